@@ -1,30 +1,48 @@
 use anyhow::Result;
 use std::fs;
 use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::{Applier, ApplyReport, ConfigKey, Registration};
+use crate::palette::{hex_to_css_rgba, Palette};
+
+const SCHEMA: &[ConfigKey] = &[
+	ConfigKey { name: "colorfile", default: "~/.config/dotfiles/ags-4/colors.scss", help: "AGS SCSS variables file to overwrite" },
+];
+
+pub fn registration() -> Registration {
+	Registration { name: "ags", schema: SCHEMA, default_enabled: true, build: |s| Ok(Box::new(AgsApplier { colorfile: s.path("colorfile")? })) }
+}
+
+pub struct AgsApplier {
+	colorfile: PathBuf,
+}
+
+impl Applier for AgsApplier {
+	fn name(&self) -> &'static str { "ags" }
+	fn config_schema(&self) -> &'static [ConfigKey] { SCHEMA }
+
+	fn apply(&self, palette: &Palette) -> Result<ApplyReport> {
+		write_colors(&self.colorfile, palette)?;
+		Ok(ApplyReport::new(format!("updated {}", self.colorfile.display())))
+	}
+
+	fn targets(&self) -> Vec<PathBuf> { vec![self.colorfile.clone()] }
+}
 
 // Write AGS SCSS colors file with primary, transparent primary (0.80), and secondary.
-// Format matches existing expectations in ags-4/colors.scss.
-pub fn apply(path: &str, primary_hex: &str, secondary_hex: &str) -> Result<()> {
-	let rgba = |hex: &str, alpha: f32| -> String {
-		if hex.len() == 7 && hex.starts_with('#') {
-			if let (Ok(r), Ok(g), Ok(b)) = (
-				u8::from_str_radix(&hex[1..3], 16),
-				u8::from_str_radix(&hex[3..5], 16),
-				u8::from_str_radix(&hex[5..7], 16),
-			) {
-				return format!("rgba({}, {}, {}, {:.2})", r, g, b, alpha);
-			}
-		}
-		format!("rgba(0,0,0,{:.2})", alpha)
-	};
-	let transparent = rgba(primary_hex, 0.80);
-	let tmp = format!("{}{}", path, ".tmp");
-	let contents = format!(
+// Format matches existing expectations in ags-4/colors.scss; the remaining palette
+// entries are appended as extra variables so widgets can opt into them.
+fn write_colors(path: &Path, palette: &Palette) -> Result<()> {
+	let transparent = hex_to_css_rgba(palette.primary(), 0.80);
+	let tmp = format!("{}{}", path.display(), ".tmp");
+	let mut contents = format!(
 		"$primary-color: {p};\n$primary-color-transparent: {pt};\n$secondary-color: {s};\n",
-		p = primary_hex,
+		p = palette.primary(),
 		pt = transparent,
-		s = secondary_hex
+		s = palette.secondary()
 	);
+	contents.push_str(&palette.scss_variables(str::to_string));
 	{
 		let mut f = fs::File::create(&tmp)?;
 		f.write_all(contents.as_bytes())?;
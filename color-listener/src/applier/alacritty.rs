@@ -0,0 +1,149 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::managed::write_atomic;
+use super::{Applier, ApplyReport, ConfigKey, Registration};
+use crate::palette::Palette;
+use crate::paths::expand_home;
+
+const SCHEMA: &[ConfigKey] = &[
+	ConfigKey { name: "config", default: "~/.config/dotfiles/alacritty/alacritty.toml", help: "alacritty.toml that should import the colors" },
+	ConfigKey { name: "colors", default: "~/.config/dotfiles/alacritty/colors.toml", help: "generated [colors] file to import" },
+];
+
+const ANSI_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+pub fn registration() -> Registration {
+	Registration {
+		name: "alacritty",
+		schema: SCHEMA,
+		default_enabled: false,
+		build: |s| Ok(Box::new(AlacrittyApplier { config: s.path("config")?, colors: s.path("colors")? })),
+	}
+}
+
+pub struct AlacrittyApplier {
+	config: PathBuf,
+	colors: PathBuf,
+}
+
+impl Applier for AlacrittyApplier {
+	fn name(&self) -> &'static str { "alacritty" }
+	fn config_schema(&self) -> &'static [ConfigKey] { SCHEMA }
+
+	// alacritty watches its config and every import (live_config_reload defaults to on), so
+	// rewriting the import is enough for open windows to pick the colors up.
+	fn apply(&self, palette: &Palette) -> Result<ApplyReport> {
+		write_atomic(&self.colors, &render_colors(palette))?;
+		let report = ApplyReport::new(format!("updated {}", self.colors.display()));
+		match ensure_import(&self.config, &self.colors) {
+			Ok(()) => Ok(report),
+			Err(e) => Ok(report.warn(format!("{e}"))),
+		}
+	}
+
+	fn targets(&self) -> Vec<PathBuf> { vec![self.config.clone(), self.colors.clone()] }
+}
+
+fn render_colors(palette: &Palette) -> String {
+	let mut out = format!(
+		"# Colors (managed by color-listener)\n\n[colors.primary]\nbackground = \"{bg}\"\nforeground = \"{fg}\"\n\n[colors.cursor]\ntext = \"{bg}\"\ncursor = \"{accent}\"\n\n[colors.selection]\ntext = \"{fg}\"\nbackground = \"{sel}\"\n",
+		bg = palette.background,
+		fg = palette.foreground,
+		accent = palette.accent,
+		sel = palette.accent_variants.dim,
+	);
+	for (table, colors) in [("normal", &palette.ansi[..8]), ("bright", &palette.ansi[8..])] {
+		out.push_str(&format!("\n[colors.{}]\n", table));
+		for (name, hex) in ANSI_NAMES.iter().zip(colors) {
+			out.push_str(&format!("{} = \"{}\"\n", name, hex));
+		}
+	}
+	out
+}
+
+// Add the colors file to `general.import`, editing alacritty.toml as text so comments and
+// layout survive. An import list that exists but lacks our file is left to the user.
+fn ensure_import(config: &Path, colors: &Path) -> Result<()> {
+	let contents = match fs::read_to_string(config) {
+		Ok(c) => c,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+		Err(e) => return Err(anyhow!("read {}: {}", config.display(), e)),
+	};
+	let imports = imports(&contents).map_err(|e| anyhow!("parse {}: {}", config.display(), e))?;
+	let base = config.parent().unwrap_or(Path::new(""));
+	let wanted = same_file_key(colors);
+	if imports.as_ref().is_some_and(|list| list.iter().any(|entry| same_file_key(&base.join(expand_home(entry))) == wanted)) {
+		return Ok(());
+	}
+
+	if imports.is_some() {
+		return Err(anyhow!("{} already has an import list; add \"{}\" to it", config.display(), colors.display()));
+	}
+	let import = format!("import = [\"{}\"]", colors.display());
+	let updated = match contents.lines().position(|l| l.trim() == "[general]") {
+		Some(i) => {
+			let mut lines: Vec<&str> = contents.lines().collect();
+			lines.insert(i + 1, &import);
+			format!("{}\n", lines.join("\n"))
+		}
+		// A table can be declared anywhere, so a new [general] goes after the user's tables.
+		None => format!("{}\n\n[general]\n{}\n", contents.trim_end(), import),
+	};
+	write_atomic(config, updated.trim_start())
+}
+
+// The import list alacritty reads: `general.import`, or the top-level `import` older releases
+// used. None when neither is set.
+fn imports(contents: &str) -> Result<Option<Vec<String>>, toml::de::Error> {
+	let table: toml::Table = toml::from_str(contents)?;
+	let list = table.get("general").and_then(|g| g.get("import")).or_else(|| table.get("import"));
+	Ok(list.map(|value| {
+		value.as_array().map(|a| a.iter().filter_map(|v| v.as_str().map(str::to_string)).collect()).unwrap_or_default()
+	}))
+}
+
+// Imports may be relative to alacritty.toml or go through a symlinked dotfiles dir; compare the
+// resolved file when it exists.
+fn same_file_key(path: &Path) -> PathBuf {
+	fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reads_general_and_legacy_imports() {
+		let general = "[general]\nimport = [\"~/a/colors.toml\", \"themes/b.toml\"]\n";
+		assert_eq!(imports(general).unwrap(), Some(vec!["~/a/colors.toml".to_string(), "themes/b.toml".to_string()]));
+		let legacy = "import = [\"colors.toml\"]\n\n[window]\nopacity = 0.9\n";
+		assert_eq!(imports(legacy).unwrap(), Some(vec!["colors.toml".to_string()]));
+		assert_eq!(imports("[window]\nopacity = 0.9\n").unwrap(), None);
+	}
+
+	#[test]
+	fn ensure_import_matches_resolved_paths() {
+		let dir = std::env::temp_dir().join(format!("cl-alacritty-{}", std::process::id()));
+		fs::create_dir_all(&dir).unwrap();
+		let (config, colors) = (dir.join("alacritty.toml"), dir.join("colors.toml"));
+		fs::write(&colors, "").unwrap();
+
+		// Mentioning the file name is not importing it.
+		fs::write(&config, "# colors.toml is generated\n[window]\nopacity = 0.9\n").unwrap();
+		ensure_import(&config, &colors).unwrap();
+		assert_eq!(imports(&fs::read_to_string(&config).unwrap()).unwrap(), Some(vec![colors.display().to_string()]));
+
+		// A relative entry resolves against alacritty.toml's directory and is left alone.
+		let relative = "[general]\nimport = [\"colors.toml\"]\n";
+		fs::write(&config, relative).unwrap();
+		ensure_import(&config, &colors).unwrap();
+		assert_eq!(fs::read_to_string(&config).unwrap(), relative);
+
+		// Same file name elsewhere is someone else's import list.
+		fs::write(&config, "[general]\nimport = [\"/elsewhere/colors.toml\"]\n").unwrap();
+		assert!(ensure_import(&config, &colors).is_err());
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
@@ -0,0 +1,94 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+
+use super::managed::write_atomic;
+use super::{Applier, ApplyReport, ConfigKey, Registration};
+use crate::color::hex_to_rgb;
+use crate::palette::{hex_to_css_rgba, Palette};
+
+const SCHEMA: &[ConfigKey] = &[
+	ConfigKey { name: "colors", default: "~/.config/dotfiles/custombar_gtk/colors.css", help: "colors.css the bar loads at runtime" },
+	ConfigKey { name: "socket", default: "/tmp/custombar_colors_socket", help: "bar socket that takes ReloadColors" },
+];
+
+pub fn registration() -> Registration {
+	Registration {
+		name: "custombar",
+		schema: SCHEMA,
+		default_enabled: false,
+		build: |s| Ok(Box::new(CustombarApplier { colors: s.path("colors")?, socket: s.path("socket")? })),
+	}
+}
+
+pub struct CustombarApplier {
+	colors: PathBuf,
+	socket: PathBuf,
+}
+
+impl Applier for CustombarApplier {
+	fn name(&self) -> &'static str { "custombar" }
+	fn config_schema(&self) -> &'static [ConfigKey] { SCHEMA }
+
+	fn apply(&self, palette: &Palette) -> Result<ApplyReport> {
+		let contents = match fs::read_to_string(&self.colors) {
+			Ok(c) => c,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+			Err(e) => return Err(anyhow!("read {}: {}", self.colors.display(), e)),
+		};
+		write_atomic(&self.colors, &set_defines(&contents, &defines(palette)))?;
+		let report = ApplyReport::new(format!("updated {}", self.colors.display()));
+		if let Err(e) = self.reload() {
+			return Ok(report.warn(format!("bar not reloaded ({}): {e}", self.socket.display())));
+		}
+		Ok(report)
+	}
+
+	fn targets(&self) -> Vec<PathBuf> { vec![self.colors.clone()] }
+
+	fn reload(&self) -> Result<()> { notify_bar(&self.socket) }
+}
+
+// The three colors the bar's widgets are styled with (see custombar_gtk/colors.css):
+// translucent accent for widget backgrounds, the same accent opaque, and the base color for text.
+fn defines(palette: &Palette) -> [(&'static str, String); 3] {
+	let (r, g, b) = hex_to_rgb(palette.accent.as_str()).unwrap_or((0, 0, 0));
+	[
+		("bg-color", hex_to_css_rgba(palette.accent.as_str(), 0.30)),
+		("bg-color-no-transparent", format!("rgb({}, {}, {})", r, g, b)),
+		("primary-color", palette.background.clone()),
+	]
+}
+
+// Rewrite `@define-color <name> ...;` lines in place (commented-out ones stay as they are) and
+// append the ones the file does not define yet.
+fn set_defines(contents: &str, defines: &[(&str, String)]) -> String {
+	let mut out = String::with_capacity(contents.len() + 128);
+	let mut seen = vec![false; defines.len()];
+	for line in contents.lines() {
+		let name = line.trim_start().strip_prefix("@define-color ").and_then(|rest| rest.split_whitespace().next());
+		match name.and_then(|n| defines.iter().position(|(d, _)| *d == n)) {
+			Some(i) => {
+				out.push_str(&format!("@define-color {} {};\n", defines[i].0, defines[i].1));
+				seen[i] = true;
+			}
+			None => {
+				out.push_str(line);
+				out.push('\n');
+			}
+		}
+	}
+	for (i, (name, value)) in defines.iter().enumerate() {
+		if !seen[i] { out.push_str(&format!("@define-color {} {};\n", name, value)); }
+	}
+	out
+}
+
+// Same one-line protocol as the bar's other sockets (utils::socket).
+fn notify_bar(socket: &Path) -> Result<()> {
+	let mut stream = UnixStream::connect(socket)?;
+	stream.write_all(b"ReloadColors")?;
+	Ok(())
+}
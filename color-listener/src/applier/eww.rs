@@ -0,0 +1,118 @@
+use anyhow::{anyhow, Result};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::managed::write_atomic;
+use super::{Applier, ApplyReport, ConfigKey, Registration};
+use crate::palette::{hex_to_css_rgba, Palette};
+
+const SCHEMA: &[ConfigKey] = &[
+	ConfigKey { name: "config", default: "~/.config/dotfiles/eww-rs/src", help: "eww-rs main config dir (EWW_CONFIG_LOC)" },
+	ConfigKey { name: "colorfile", default: "colors.scss", help: "SCSS variables file, relative to config" },
+	ConfigKey {
+		name: "widgets",
+		default: "widgets/capture/capture-dropdown,widgets/audio/audio-dropdown,widgets/brightness/brightness-dropdown",
+		help: "comma-separated dropdown config dirs (relative to config) to reload too",
+	},
+];
+
+/// Budget for one `eww reload`; apply runs it once per config dir with the daemon locked, so a
+/// wedged eww is killed after this instead of stalling every later wallpaper.
+const RELOAD_TIMEOUT: Duration = Duration::from_secs(3);
+const POLL: Duration = Duration::from_millis(20);
+
+pub fn registration() -> Registration {
+	Registration {
+		name: "eww",
+		schema: SCHEMA,
+		default_enabled: false,
+		build: |s| {
+			let config = s.path("config")?;
+			let colorfile = config.join(s.get("colorfile")?);
+			let mut dirs = vec![config.clone()];
+			dirs.extend(s.get("widgets")?.split(',').map(str::trim).filter(|d| !d.is_empty()).map(|d| config.join(d)));
+			Ok(Box::new(EwwApplier { colorfile, dirs }))
+		},
+	}
+}
+
+pub struct EwwApplier {
+	colorfile: PathBuf,
+	// Every eww config dir eww-rs runs: the bar first, then the dropdowns.
+	dirs: Vec<PathBuf>,
+}
+
+impl Applier for EwwApplier {
+	fn name(&self) -> &'static str { "eww" }
+	fn config_schema(&self) -> &'static [ConfigKey] { SCHEMA }
+
+	fn apply(&self, palette: &Palette) -> Result<ApplyReport> {
+		write_atomic(&self.colorfile, &render_variables(palette))?;
+		let mut report = ApplyReport::new(format!("updated {}", self.colorfile.display()));
+		for dir in &self.dirs {
+			if let Err(e) = reload_config(dir) {
+				report = report.warn(format!("{} not reloaded: {e}", dir.display()));
+			}
+		}
+		Ok(report)
+	}
+
+	fn targets(&self) -> Vec<PathBuf> { vec![self.colorfile.clone()] }
+
+	fn reload(&self) -> Result<()> {
+		for dir in &self.dirs {
+			reload_config(dir)?;
+		}
+		Ok(())
+	}
+}
+
+// Same base names as the AGS colors.scss, plus the two translucent backgrounds eww-rs uses:
+// the bar (accent) and the dropdown panels (background).
+fn render_variables(palette: &Palette) -> String {
+	let mut contents = format!(
+		"$primary-color: {p};\n$secondary-color: {s};\n$bar-background: {bar};\n$dropdown-background: {dropdown};\n",
+		p = palette.primary(),
+		s = palette.secondary(),
+		bar = hex_to_css_rgba(palette.secondary(), 0.326),
+		dropdown = hex_to_css_rgba(palette.primary(), 0.728),
+	);
+	contents.push_str(&palette.scss_variables(str::to_string));
+	contents
+}
+
+// `eww reload` only reaches a running daemon; dropdowns that are closed just report an error.
+fn reload_config(dir: &Path) -> Result<()> {
+	let mut child = Command::new("eww").arg("-c").arg(dir).arg("reload")
+		.stdin(Stdio::null())
+		.stdout(Stdio::null())
+		.stderr(Stdio::piped())
+		.spawn()
+		.map_err(|e| anyhow!("run eww: {e}"))?;
+	// Drained on the side so a chatty eww cannot block on a full pipe while we wait.
+	let mut stderr_pipe = child.stderr.take();
+	let stderr = thread::spawn(move || {
+		let mut text = String::new();
+		if let Some(pipe) = stderr_pipe.as_mut() { let _ = pipe.read_to_string(&mut text); }
+		text
+	});
+
+	let deadline = Instant::now() + RELOAD_TIMEOUT;
+	let status = loop {
+		if let Some(status) = child.try_wait()? { break status; }
+		if Instant::now() >= deadline {
+			let _ = child.kill();
+			let _ = child.wait();
+			return Err(anyhow!("eww reload timed out after {:?}", RELOAD_TIMEOUT));
+		}
+		thread::sleep(POLL);
+	};
+	if !status.success() {
+		let stderr = stderr.join().unwrap_or_default();
+		return Err(anyhow!("eww reload failed: {}", stderr.trim()));
+	}
+	Ok(())
+}
@@ -0,0 +1,283 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use super::managed::write_atomic;
+use super::{Applier, ApplyReport, ConfigKey, Registration};
+use crate::color::hex_to_rgb;
+use crate::palette::Palette;
+
+const SCHEMA: &[ConfigKey] = &[
+	ConfigKey { name: "config", default: "~/.config/dotfiles/fastfetch/config.jsonc", help: "fastfetch config.jsonc whose display.color is updated" },
+];
+
+pub fn registration() -> Registration {
+	Registration { name: "fastfetch", schema: SCHEMA, default_enabled: false, build: |s| Ok(Box::new(FastfetchApplier { config: s.path("config")? })) }
+}
+
+pub struct FastfetchApplier {
+	config: PathBuf,
+}
+
+impl Applier for FastfetchApplier {
+	fn name(&self) -> &'static str { "fastfetch" }
+	fn config_schema(&self) -> &'static [ConfigKey] { SCHEMA }
+
+	fn apply(&self, palette: &Palette) -> Result<ApplyReport> {
+		let contents = fs::read_to_string(&self.config)
+			.map_err(|e| anyhow!("read {}: {}", self.config.display(), e))?;
+		let colors = [("keys", sgr(&palette.accent)), ("title", sgr(&palette.accent_variants.bright))];
+		let updated = set_display_color(&contents, &colors)
+			.map_err(|e| anyhow!("{}: {}", self.config.display(), e))?;
+		write_atomic(&self.config, &updated)?;
+		Ok(ApplyReport::new(format!("updated display.color in {}", self.config.display())))
+	}
+
+	fn targets(&self) -> Vec<PathBuf> { vec![self.config.clone()] }
+}
+
+// fastfetch passes color strings through as SGR parameters, so 24-bit colors are `38;2;r;g;b`.
+fn sgr(hex: &str) -> String {
+	let (r, g, b) = hex_to_rgb(hex).unwrap_or((0, 0, 0));
+	format!("38;2;{};{};{}", r, g, b)
+}
+
+// The config is JSONC, so instead of a serde round trip (which would drop comments and
+// reformat the file) the edit works on byte offsets: find the member, splice in the new value.
+
+// `contents` with `display.color.<key>` set for every entry of `colors`.
+fn set_display_color(contents: &str, colors: &[(&str, String)]) -> Result<String> {
+	let root = skip_trivia(contents.as_bytes(), 0);
+	if contents.as_bytes().get(root) != Some(&b'{') {
+		return Err(anyhow!("expected a JSON object"));
+	}
+	set_path(contents, root, &["display", "color"], colors)
+}
+
+struct Member {
+	key: String,
+	value: (usize, usize),
+}
+
+// Set string `values` in the object reached by following `path` from the object at `open`,
+// creating the missing objects along the way.
+fn set_path(src: &str, open: usize, path: &[&str], values: &[(&str, String)]) -> Result<String> {
+	let members = members(src.as_bytes(), open)?;
+	let indent = format!("{}    ", line_indent(src, open));
+	match path.split_first() {
+		Some((key, rest)) => match members.iter().find(|m| m.key == *key) {
+			Some(m) if src.as_bytes()[m.value.0] == b'{' => set_path(src, m.value.0, rest, values),
+			// e.g. `"color": "blue"`, the shorthand for every color; replace it with an object.
+			Some(m) => Ok(format!("{}{}{}", &src[..m.value.0], render_object(rest, values, &indent), &src[m.value.1..])),
+			None => Ok(insert_members(src, open, !members.is_empty(), &[(key, render_object(rest, values, &indent))], &indent)),
+		},
+		None => {
+			let mut out = src.to_string();
+			let mut missing = Vec::new();
+			let mut replacements = Vec::new();
+			for (key, value) in values {
+				match members.iter().find(|m| m.key == *key) {
+					Some(m) => replacements.push((m.value, quote(value))),
+					None => missing.push((*key, quote(value))),
+				}
+			}
+			// Back to front so earlier offsets stay valid; insertions happen at `open`, before all of them.
+			replacements.sort_by_key(|r| std::cmp::Reverse(r.0.0));
+			for ((start, end), value) in replacements {
+				out.replace_range(start..end, &value);
+			}
+			if !missing.is_empty() {
+				out = insert_members(&out, open, !members.is_empty(), &missing, &indent);
+			}
+			Ok(out)
+		}
+	}
+}
+
+fn insert_members(src: &str, open: usize, has_members: bool, new: &[(&str, String)], indent: &str) -> String {
+	let mut text: String = new.iter().map(|(k, v)| format!("\n{}\"{}\": {}", indent, k, v)).collect::<Vec<_>>().join(",");
+	if has_members {
+		text.push(',');
+	} else {
+		text.push('\n');
+		text.push_str(&indent[..indent.len() - 4]);
+	}
+	format!("{}{}{}", &src[..=open], text, &src[open + 1..])
+}
+
+// An object holding `path` nested down to `values`, closing at `indent`.
+fn render_object(path: &[&str], values: &[(&str, String)], indent: &str) -> String {
+	let inner = format!("{}    ", indent);
+	let body: Vec<String> = match path.split_first() {
+		Some((key, rest)) => vec![format!("{}\"{}\": {}", inner, key, render_object(rest, values, &inner))],
+		None => values.iter().map(|(k, v)| format!("{}\"{}\": {}", inner, k, quote(v))).collect(),
+	};
+	format!("{{\n{}\n{}}}", body.join(",\n"), indent)
+}
+
+fn quote(value: &str) -> String { format!("\"{}\"", value) }
+
+fn line_indent(src: &str, pos: usize) -> &str {
+	let start = src[..pos].rfind('\n').map(|i| i + 1).unwrap_or(0);
+	let line = &src[start..pos];
+	&line[..line.len() - line.trim_start().len()]
+}
+
+// Members of the object whose `{` is at `open`.
+fn members(src: &[u8], open: usize) -> Result<Vec<Member>> {
+	let mut out = Vec::new();
+	let mut i = open + 1;
+	loop {
+		i = skip_trivia(src, i);
+		match src.get(i) {
+			Some(b'}') => return Ok(out),
+			Some(b'"') => {
+				let key_end = skip_string(src, i)?;
+				let key = String::from_utf8_lossy(&src[i + 1..key_end - 1]).to_string();
+				i = skip_trivia(src, key_end);
+				if src.get(i) != Some(&b':') { return Err(anyhow!("expected ':' after \"{}\"", key)); }
+				let start = skip_trivia(src, i + 1);
+				let end = skip_value(src, start)?;
+				out.push(Member { key, value: (start, end) });
+				i = skip_trivia(src, end);
+				if src.get(i) == Some(&b',') { i += 1; }
+			}
+			_ => return Err(anyhow!("unexpected input at byte {}", i)),
+		}
+	}
+}
+
+// Whitespace plus `//` and `/* */` comments.
+fn skip_trivia(src: &[u8], mut i: usize) -> usize {
+	loop {
+		while src.get(i).is_some_and(|c| c.is_ascii_whitespace()) { i += 1; }
+		if src[i.min(src.len())..].starts_with(b"//") {
+			while src.get(i).is_some_and(|c| *c != b'\n') { i += 1; }
+		} else if src[i.min(src.len())..].starts_with(b"/*") {
+			i = src[i + 2..].windows(2).position(|w| w == b"*/").map(|p| i + 2 + p + 2).unwrap_or(src.len());
+		} else {
+			return i;
+		}
+	}
+}
+
+// Index just past the closing quote of the string starting at `i`.
+fn skip_string(src: &[u8], i: usize) -> Result<usize> {
+	let mut j = i + 1;
+	while j < src.len() {
+		match src[j] {
+			b'\\' => j += 2,
+			b'"' => return Ok(j + 1),
+			_ => j += 1,
+		}
+	}
+	Err(anyhow!("unterminated string at byte {}", i))
+}
+
+// Index just past the value starting at `i`.
+fn skip_value(src: &[u8], i: usize) -> Result<usize> {
+	match src.get(i) {
+		Some(b'"') => skip_string(src, i),
+		Some(b'{') | Some(b'[') => {
+			let mut depth = 0;
+			let mut j = i;
+			while j < src.len() {
+				match src[j] {
+					b'"' => { j = skip_string(src, j)?; continue; }
+					b'/' => { j = skip_trivia(src, j).max(j + 1); continue; }
+					b'{' | b'[' => depth += 1,
+					b'}' | b']' => {
+						depth -= 1;
+						if depth == 0 { return Ok(j + 1); }
+					}
+					_ => {}
+				}
+				j += 1;
+			}
+			Err(anyhow!("unterminated value at byte {}", i))
+		}
+		Some(_) => {
+			let len = src[i..].iter().position(|c| matches!(c, b',' | b'}' | b']' | b'/') || c.is_ascii_whitespace()).unwrap_or(src.len() - i);
+			Ok(i + len)
+		}
+		None => Err(anyhow!("unexpected end of input")),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn colors() -> Vec<(&'static str, String)> {
+		vec![("keys", "38;2;1;2;3".to_string()), ("title", "38;2;4;5;6".to_string())]
+	}
+
+	// Raw text of the value at `path`, found with the same scanner the edit uses.
+	fn lookup<'a>(src: &'a str, path: &[&str]) -> Option<&'a str> {
+		let mut open = skip_trivia(src.as_bytes(), 0);
+		let mut value = None;
+		for key in path {
+			let member = members(src.as_bytes(), open).ok()?.into_iter().find(|m| m.key == *key)?;
+			open = member.value.0;
+			value = Some(&src[member.value.0..member.value.1]);
+		}
+		value
+	}
+
+	fn assert_colors(out: &str) {
+		assert_eq!(lookup(out, &["display", "color", "keys"]), Some("\"38;2;1;2;3\""), "{out}");
+		assert_eq!(lookup(out, &["display", "color", "title"]), Some("\"38;2;4;5;6\""), "{out}");
+	}
+
+	#[test]
+	fn repo_config_without_display() {
+		let src = include_str!("../../../fastfetch/config.jsonc");
+		let out = set_display_color(src, &colors()).unwrap();
+		assert_colors(&out);
+		// Plain JSON stays plain JSON, and nothing else moved.
+		let (before, after): (serde_json::Value, serde_json::Value) = (serde_json::from_str(src).unwrap(), serde_json::from_str(&out).unwrap());
+		assert_eq!(before["modules"], after["modules"]);
+		assert_eq!(before["logo"], after["logo"]);
+		assert_eq!(after["display"]["color"]["keys"], "38;2;1;2;3");
+	}
+
+	#[test]
+	fn color_shorthand_becomes_object() {
+		let src = "{\n    \"display\": {\n        \"color\": \"blue\",\n        \"separator\": \" -> \"\n    }\n}\n";
+		let out = set_display_color(src, &colors()).unwrap();
+		assert_colors(&out);
+		assert!(!out.contains("blue"), "{out}");
+		assert_eq!(lookup(&out, &["display", "separator"]), Some("\" -> \""));
+		serde_json::from_str::<serde_json::Value>(&out).unwrap();
+	}
+
+	#[test]
+	fn existing_color_object_is_updated_in_place() {
+		let src = "{\n    \"display\": {\n        \"color\": {\n            \"keys\": \"red\",\n            \"output\": \"white\"\n        }\n    }\n}\n";
+		let out = set_display_color(src, &colors()).unwrap();
+		assert_colors(&out);
+		assert_eq!(lookup(&out, &["display", "color", "output"]), Some("\"white\""));
+		assert_eq!(out.matches("\"keys\"").count(), 1, "{out}");
+		serde_json::from_str::<serde_json::Value>(&out).unwrap();
+	}
+
+	#[test]
+	fn comments_and_slashes_in_strings_survive() {
+		let src = r#"{
+    // fastfetch config
+    "$schema": "https://example.org/schema.json", /* inline */
+    "logo": { "source": "~/a//b.txt" }, // trailing
+    "display": {
+        /* no color yet */
+        "separator": "//"
+    }
+}
+"#;
+		let out = set_display_color(src, &colors()).unwrap();
+		assert_colors(&out);
+		for kept in ["// fastfetch config", "/* inline */", "// trailing", "/* no color yet */", "\"~/a//b.txt\"", "\"https://example.org/schema.json\""] {
+			assert!(out.contains(kept), "lost {kept}: {out}");
+		}
+		assert_eq!(lookup(&out, &["display", "separator"]), Some("\"//\""));
+	}
+}
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use super::managed::{write_atomic, ManagedBlock};
+use super::{Applier, ApplyReport, ConfigKey, Registration};
+use crate::palette::Palette;
+
+const SCHEMA: &[ConfigKey] = &[
+	ConfigKey { name: "config", default: "~/.config/foot/foot.ini", help: "foot.ini that should include the colors" },
+	ConfigKey { name: "colors", default: "~/.config/foot/colors-generated.ini", help: "generated [colors] file to include" },
+];
+
+// `include=` is only valid in the main section, so the block has to stay above any [section].
+const BLOCK: ManagedBlock = ManagedBlock {
+	begin: "# >>> color-listener >>>",
+	end: "# <<< color-listener <<<",
+};
+
+pub fn registration() -> Registration {
+	Registration {
+		name: "foot",
+		schema: SCHEMA,
+		default_enabled: false,
+		build: |s| Ok(Box::new(FootApplier { config: s.path("config")?, colors: s.path("colors")? })),
+	}
+}
+
+pub struct FootApplier {
+	config: PathBuf,
+	colors: PathBuf,
+}
+
+impl Applier for FootApplier {
+	fn name(&self) -> &'static str { "foot" }
+	fn config_schema(&self) -> &'static [ConfigKey] { SCHEMA }
+
+	fn apply(&self, palette: &Palette) -> Result<ApplyReport> {
+		write_atomic(&self.colors, &render_colors(palette))?;
+		BLOCK.write(&self.config, &format!("include={}", self.colors.display()))?;
+		// foot has no config reload; running windows keep their colors.
+		Ok(ApplyReport::new(format!("updated {} (applies to new foot windows)", self.colors.display())))
+	}
+
+	fn targets(&self) -> Vec<PathBuf> { vec![self.config.clone(), self.colors.clone()] }
+}
+
+// foot wants bare RRGGBB values.
+fn render_colors(palette: &Palette) -> String {
+	let bare = |hex: &str| hex.trim_start_matches('#').to_string();
+	let mut out = format!(
+		"# Colors (managed by color-listener)\n[colors]\nbackground={bg}\nforeground={fg}\ncursor={bg} {accent}\nselection-foreground={fg}\nselection-background={sel}\nurls={accent}\n",
+		bg = bare(&palette.background),
+		fg = bare(&palette.foreground),
+		accent = bare(&palette.accent),
+		sel = bare(&palette.accent_variants.dim),
+	);
+	for (i, hex) in palette.ansi.iter().enumerate() {
+		let key = if i < 8 { format!("regular{}", i) } else { format!("bright{}", i - 8) };
+		out.push_str(&format!("{}={}\n", key, bare(hex)));
+	}
+	out
+}
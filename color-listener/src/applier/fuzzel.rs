@@ -0,0 +1,57 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use super::managed::{set_section_keys, write_atomic};
+use super::{Applier, ApplyReport, ConfigKey, Registration};
+use crate::color::hex_to_rgb;
+use crate::palette::Palette;
+
+const SCHEMA: &[ConfigKey] = &[
+	ConfigKey { name: "config", default: "~/.config/dotfiles/fuzzel/fuzzel.ini", help: "fuzzel.ini whose [colors] section is updated" },
+];
+
+pub fn registration() -> Registration {
+	Registration { name: "fuzzel", schema: SCHEMA, default_enabled: false, build: |s| Ok(Box::new(FuzzelApplier { config: s.path("config")? })) }
+}
+
+pub struct FuzzelApplier {
+	config: PathBuf,
+}
+
+impl Applier for FuzzelApplier {
+	fn name(&self) -> &'static str { "fuzzel" }
+	fn config_schema(&self) -> &'static [ConfigKey] { SCHEMA }
+
+	fn apply(&self, palette: &Palette) -> Result<ApplyReport> {
+		let contents = fs::read_to_string(&self.config)
+			.map_err(|e| anyhow!("read {}: {}", self.config.display(), e))?;
+		write_atomic(&self.config, &set_section_keys(&contents, "colors", &colors(palette)))?;
+		// fuzzel reads its config on every launch, so there is nothing to reload.
+		Ok(ApplyReport::new(format!("updated [colors] in {}", self.config.display())))
+	}
+
+	fn targets(&self) -> Vec<PathBuf> { vec![self.config.clone()] }
+}
+
+// Same roles as the hand-written section: opaque background, accent for matches and the
+// border, and a translucent accent (0x53, as before) behind the selected entry.
+fn colors(palette: &Palette) -> Vec<(&'static str, String)> {
+	vec![
+		("background", rgba(&palette.background, 0xff)),
+		("text", rgba(&palette.foreground, 0xff)),
+		("prompt", rgba(&palette.foreground, 0xff)),
+		("placeholder", rgba(&palette.accent_variants.muted, 0xff)),
+		("input", rgba(&palette.foreground, 0xff)),
+		("match", rgba(&palette.accent, 0xff)),
+		("selection", rgba(&palette.accent, 0x53)),
+		("selection-text", rgba(&palette.foreground, 0xff)),
+		("border", rgba(&palette.accent, 0xff)),
+	]
+}
+
+// fuzzel wants RRGGBBAA without a leading `#`.
+fn rgba(hex: &str, alpha: u8) -> String {
+	let (r, g, b) = hex_to_rgb(hex).unwrap_or((0, 0, 0));
+	format!("{:02x}{:02x}{:02x}{:02x}", r, g, b, alpha)
+}
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use super::managed::ManagedBlock;
+use super::{Applier, ApplyReport, ConfigKey, Registration};
+use crate::color::contrast_ratio_hex;
+use crate::palette::Palette;
+
+const SCHEMA: &[ConfigKey] = &[
+	ConfigKey { name: "gtk4", default: "~/.config/gtk-4.0/gtk.css", help: "GTK4/libadwaita user stylesheet (empty to skip)" },
+	ConfigKey { name: "gtk3", default: "~/.config/gtk-3.0/gtk.css", help: "GTK3 user stylesheet (empty to skip)" },
+];
+
+const BLOCK: ManagedBlock = ManagedBlock {
+	begin: "/* >>> color-listener >>> (generated, edits inside this block are overwritten) */",
+	end: "/* <<< color-listener <<< */",
+};
+
+pub fn registration() -> Registration {
+	Registration {
+		name: "gtk",
+		schema: SCHEMA,
+		default_enabled: false,
+		build: |s| {
+			let optional = |key: &str| -> Result<Option<PathBuf>> {
+				if s.get(key)?.trim().is_empty() { Ok(None) } else { Ok(Some(s.path(key)?)) }
+			};
+			Ok(Box::new(GtkApplier { stylesheets: [optional("gtk4")?, optional("gtk3")?].into_iter().flatten().collect() }))
+		},
+	}
+}
+
+pub struct GtkApplier {
+	stylesheets: Vec<PathBuf>,
+}
+
+impl Applier for GtkApplier {
+	fn name(&self) -> &'static str { "gtk" }
+	fn config_schema(&self) -> &'static [ConfigKey] { SCHEMA }
+
+	fn apply(&self, palette: &Palette) -> Result<ApplyReport> {
+		let body = render_defines(palette);
+		for path in &self.stylesheets {
+			BLOCK.write(path, &body)?;
+		}
+		let written: Vec<String> = self.stylesheets.iter().map(|p| p.display().to_string()).collect();
+		// GTK only reads gtk.css at startup; running apps keep their colors until restarted.
+		Ok(ApplyReport::new(format!("updated {} (applies to newly started apps)", written.join(", "))))
+	}
+
+	fn targets(&self) -> Vec<PathBuf> { self.stylesheets.clone() }
+}
+
+// One block for both toolkits: libadwaita named colors (GTK4) and the Adwaita theme_* colors
+// (GTK3). Each toolkit ignores the names it does not know.
+fn render_defines(palette: &Palette) -> String {
+	let bg = palette.background.as_str();
+	let surface = palette.surface.as_str();
+	let fg = palette.foreground.as_str();
+	let accent = palette.accent.as_str();
+	let on_accent = readable_on(accent, bg, fg);
+	let red = palette.ansi[1].as_str();
+	let on_red = readable_on(red, bg, fg);
+
+	let defines: [(&str, &str); 32] = [
+		// libadwaita
+		("accent_color", accent),
+		("accent_bg_color", accent),
+		("accent_fg_color", on_accent),
+		("destructive_color", palette.ansi[9].as_str()),
+		("destructive_bg_color", red),
+		("destructive_fg_color", on_red),
+		("success_color", palette.ansi[2].as_str()),
+		("warning_color", palette.ansi[3].as_str()),
+		("error_color", red),
+		("window_bg_color", bg),
+		("window_fg_color", fg),
+		("view_bg_color", bg),
+		("view_fg_color", fg),
+		("headerbar_bg_color", surface),
+		("headerbar_fg_color", fg),
+		("headerbar_backdrop_color", bg),
+		("sidebar_bg_color", surface),
+		("sidebar_fg_color", fg),
+		("card_bg_color", surface),
+		("card_fg_color", fg),
+		("popover_bg_color", surface),
+		("popover_fg_color", fg),
+		("dialog_bg_color", surface),
+		("dialog_fg_color", fg),
+		// GTK3 Adwaita
+		("theme_bg_color", bg),
+		("theme_fg_color", fg),
+		("theme_base_color", bg),
+		("theme_text_color", fg),
+		("theme_selected_bg_color", accent),
+		("theme_selected_fg_color", on_accent),
+		("insensitive_bg_color", surface),
+		("borders", surface),
+	];
+	let mut out = String::new();
+	for (name, hex) in defines {
+		out.push_str(&format!("@define-color {} {};\n", name, hex));
+	}
+	out
+}
+
+/// Whichever of the two palette ends reads better on `fill`.
+fn readable_on<'a>(fill: &str, bg: &'a str, fg: &'a str) -> &'a str {
+	if contrast_ratio_hex(fill, bg) >= contrast_ratio_hex(fill, fg) { bg } else { fg }
+}
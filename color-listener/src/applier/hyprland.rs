@@ -1,50 +1,181 @@
 use anyhow::{anyhow, Result};
 use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{Applier, ApplyReport, ConfigKey, Registration};
+use crate::color::hex_to_rgb;
+use crate::hypr_ipc;
+use crate::palette::Palette;
+
+const SCHEMA: &[ConfigKey] = &[
+	ConfigKey { name: "colors", default: "~/.config/dotfiles/hypr/colors.conf", help: "Hyprland colors.conf holding the border variables" },
+	ConfigKey { name: "live", default: "true", help: "also set the colors over Hyprland IPC so they apply at once" },
+	ConfigKey { name: "groups", default: "false", help: "theme group borders and the groupbar too" },
+	ConfigKey { name: "gradient", default: "false", help: "active border as a gradient of gradient_colors" },
+	ConfigKey { name: "gradient_colors", default: "accent,accent-dim", help: "two palette color names for the gradient" },
+	ConfigKey { name: "gradient_angle", default: "45", help: "gradient angle in degrees" },
+];
+
+// colors.conf variable -> the Hyprland options it feeds (hyprland.conf references the variables;
+// the IPC push sets the options directly).
+const ACTIVE: &str = "$hyprland_active_border_color";
+const INACTIVE: &str = "$hyprland_inactive_border_color";
+const GROUP_ACTIVE: &str = "$hyprland_group_active_color";
+const GROUP_INACTIVE: &str = "$hyprland_group_inactive_color";
+const KEYWORDS: &[(&str, &[&str])] = &[
+	(ACTIVE, &["general:col.active_border"]),
+	(INACTIVE, &["general:col.inactive_border"]),
+	(GROUP_ACTIVE, &["group:col.border_active", "group:groupbar:col.active"]),
+	(GROUP_INACTIVE, &["group:col.border_inactive", "group:groupbar:col.inactive"]),
+];
+
+pub fn registration() -> Registration {
+	Registration {
+		name: "hyprland",
+		schema: SCHEMA,
+		default_enabled: true,
+		build: |s| {
+			let gradient = if s.flag("gradient")? {
+				let names: Vec<String> = s.get("gradient_colors")?.split(',').map(|n| n.trim().to_string()).collect();
+				let [from, to]: [String; 2] = names.try_into()
+					.map_err(|_| anyhow!("hyprland.gradient_colors needs exactly two palette color names"))?;
+				let angle = s.get("gradient_angle")?.trim().trim_end_matches("deg").parse::<i32>()
+					.map_err(|_| anyhow!("hyprland.gradient_angle must be a whole number of degrees"))?;
+				Some(Gradient { from, to, angle })
+			} else {
+				None
+			};
+			Ok(Box::new(HyprlandApplier { colors: s.path("colors")?, live: s.flag("live")?, groups: s.flag("groups")?, gradient }))
+		},
+	}
+}
+
+pub struct HyprlandApplier {
+	colors: PathBuf,
+	live: bool,
+	groups: bool,
+	gradient: Option<Gradient>,
+}
+
+struct Gradient {
+	from: String,
+	to: String,
+	angle: i32,
+}
+
+impl Applier for HyprlandApplier {
+	fn name(&self) -> &'static str { "hyprland" }
+	fn config_schema(&self) -> &'static [ConfigKey] { SCHEMA }
+
+	fn apply(&self, palette: &Palette) -> Result<ApplyReport> {
+		let values = self.values(palette)?;
+		update_colors_conf(&self.colors, &values)?;
+		let report = ApplyReport::new(format!("updated {}", self.colors.display()));
+		// Outside a Hyprland session there is nothing to update live.
+		if self.live && hypr_ipc::running() && let Err(e) = push_live(&values) {
+			return Ok(report.warn(format!("live update skipped: {e}")));
+		}
+		Ok(report)
+	}
+
+	fn targets(&self) -> Vec<PathBuf> { vec![self.colors.clone()] }
+
+	// After a rollback colors.conf holds the old values again; push whatever it says.
+	fn reload(&self) -> Result<()> {
+		if !self.live || !hypr_ipc::running() { return Ok(()); }
+		let contents = fs::read_to_string(&self.colors)
+			.map_err(|e| anyhow!("read {}: {}", self.colors.display(), e))?;
+		let values: Vec<(&str, String)> = KEYWORDS.iter()
+			.filter_map(|(var, _)| get_var(&contents, var).map(|v| (*var, v)))
+			.collect();
+		push_live(&values)
+	}
+}
+
+impl HyprlandApplier {
+	// Variable values: active gets the accent (or the gradient), inactive gets the background.
+	fn values(&self, palette: &Palette) -> Result<Vec<(&'static str, String)>> {
+		let active = match &self.gradient {
+			Some(g) => format!(
+				"rgba({}) rgba({}) {}deg",
+				hex_to_rgba_packed(named_color(palette, &g.from)?, 0xFF),
+				hex_to_rgba_packed(named_color(palette, &g.to)?, 0xFF),
+				g.angle,
+			),
+			None => format!("rgba({})", hex_to_rgba_packed(palette.secondary(), 0xFF)),
+		};
+		let inactive = format!("rgba({})", hex_to_rgba_packed(palette.primary(), 0xAA)); // use 0xAA alpha to mimic example
+		let mut values = vec![(ACTIVE, active.clone()), (INACTIVE, inactive.clone())];
+		if self.groups {
+			values.push((GROUP_ACTIVE, active));
+			values.push((GROUP_INACTIVE, inactive));
+		}
+		Ok(values)
+	}
+}
+
+fn named_color<'a>(palette: &'a Palette, name: &str) -> Result<&'a str> {
+	palette.named_colors().into_iter()
+		.find(|(n, _)| n == name)
+		.map(|(_, hex)| hex)
+		.ok_or_else(|| anyhow!("unknown palette color {} in hyprland.gradient_colors", name))
+}
+
+// Set the options behind each variable in one IPC batch.
+fn push_live(values: &[(&str, String)]) -> Result<()> {
+	let mut pairs = Vec::new();
+	for (var, value) in values {
+		if let Some((_, keywords)) = KEYWORDS.iter().find(|(v, _)| v == var) {
+			pairs.extend(keywords.iter().map(|k| (*k, value.clone())));
+		}
+	}
+	hypr_ipc::keywords(&pairs)
+}
 
 // Update variables in a Hyprland colors.conf-like file:
 // $hyprland_active_border_color = rgba(rrrrggggbbbb aaaa)
 // $hyprland_inactive_border_color = rgba(rrrrggggbbbb aaaa)
-// active gets secondary, inactive gets primary.
-pub fn apply(conf_path: &str, primary_hex: &str, secondary_hex: &str) -> Result<()> {
+fn update_colors_conf(conf_path: &Path, values: &[(&str, String)]) -> Result<()> {
 	let mut contents = fs::read_to_string(conf_path)
-		.map_err(|e| anyhow!("read {}: {}", conf_path, e))?;
-
-	let active = hex_to_rgba_packed(secondary_hex, 0xFF);
-	let inactive = hex_to_rgba_packed(primary_hex, 0xAA); // use 0xAA alpha to mimic example
+		.map_err(|e| anyhow!("read {}: {}", conf_path.display(), e))?;
 
-	contents = set_var(&contents, "$hyprland_active_border_color", &format!("rgba({})", active));
-	contents = set_var(&contents, "$hyprland_inactive_border_color", &format!("rgba({})", inactive));
+	for (var, value) in values {
+		contents = set_var(&contents, var, value);
+	}
 
-	let tmp = format!("{}{}", conf_path, ".tmp");
+	let tmp = format!("{}{}", conf_path.display(), ".tmp");
 	fs::write(&tmp, contents)?;
 	fs::rename(&tmp, conf_path)?;
 	Ok(())
 }
 
 fn set_var(input: &str, var: &str, value: &str) -> String {
-	// replace whole line starting with var = ...
+	// replace whole line starting with var = ..., append it if missing
 	let mut out = String::with_capacity(input.len()+64);
+	let mut found = false;
 	for line in input.lines() {
 		if line.trim_start().starts_with(var) {
 			out.push_str(&format!("{} = {}\n", var, value));
+			found = true;
 		} else {
 			out.push_str(line);
 			out.push('\n');
 		}
 	}
+	if !found { out.push_str(&format!("{} = {}\n", var, value)); }
 	out
 }
 
+fn get_var(input: &str, var: &str) -> Option<String> {
+	input.lines()
+		.filter_map(|l| l.trim_start().strip_prefix(var))
+		.filter_map(|rest| rest.trim_start().strip_prefix('='))
+		.map(|v| v.trim().to_string())
+		.next_back()
+}
+
 fn hex_to_rgba_packed(hex: &str, alpha: u8) -> String {
-	if hex.len()==7 && hex.starts_with('#') {
-		if let (Ok(r),Ok(g),Ok(b)) = (
-			u8::from_str_radix(&hex[1..3],16),
-			u8::from_str_radix(&hex[3..5],16),
-			u8::from_str_radix(&hex[5..7],16)) {
-			return format!("{:02x}{:02x}{:02x}{:02x}", r,g,b,alpha);
-		}
-	}
 	// default opaque black
-	format!("000000{:02x}", alpha)
+	let (r, g, b) = hex_to_rgb(hex).unwrap_or((0, 0, 0));
+	format!("{:02x}{:02x}{:02x}{:02x}", r, g, b, alpha)
 }
-
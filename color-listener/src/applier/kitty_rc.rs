@@ -0,0 +1,163 @@
+// Minimal kitty remote-control client. kitty accepts `ESC P @kitty-cmd <json> ESC \` on the
+// sockets it opens for `listen_on`, so appliers talk to every instance directly instead of
+// spawning `kitty @` once per socket.
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Per-socket budget for connect, send and reply; a hung instance is skipped after this.
+pub const TIMEOUT: Duration = Duration::from_secs(2);
+
+const PREFIX: &[u8] = b"\x1bP@kitty-cmd";
+const SUFFIX: &[u8] = b"\x1b\\";
+// Protocol version sent with every command; kitty only uses it to reject clients that are newer
+// than itself, so an old value keeps working with every kitty that has these commands.
+const VERSION: [u32; 3] = [0, 26, 0];
+
+/// One kitty instance's control socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Socket {
+	Path(PathBuf),
+	/// `unix:@name`, a Linux abstract socket.
+	Abstract(String),
+}
+
+impl std::fmt::Display for Socket {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Socket::Path(p) => write!(f, "unix:{}", p.display()),
+			Socket::Abstract(name) => write!(f, "unix:@{}", name),
+		}
+	}
+}
+
+/// Every socket behind a `listen_on` value. kitty appends `-<pid>` to the configured path for each
+/// instance, so `unix:/tmp/kitty-me` (or one instance's `unix:/tmp/kitty-me-1234`) finds
+/// `/tmp/kitty-me`, `/tmp/kitty-me-1234`, `/tmp/kitty-me-5678`, ...
+pub fn discover(listen_on: &str) -> Vec<Socket> {
+	let Some(target) = listen_on.trim().strip_prefix("unix:") else { return Vec::new() };
+	if let Some(name) = target.strip_prefix('@') {
+		return vec![Socket::Abstract(name.to_string())];
+	}
+	let path = Path::new(target);
+	let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) else { return Vec::new() };
+	let file_name = file_name.to_string_lossy();
+	let base = match file_name.rsplit_once('-') {
+		Some((base, pid)) if !pid.is_empty() && pid.bytes().all(|b| b.is_ascii_digit()) => base,
+		_ => file_name.as_ref(),
+	};
+	let mut out = Vec::new();
+	if let Ok(read) = fs::read_dir(parent) {
+		for entry in read.flatten() {
+			let name = entry.file_name();
+			let name = name.to_string_lossy();
+			let matches = name == base || name.strip_prefix(base).is_some_and(|rest| rest.starts_with('-'));
+			if matches && entry.file_type().is_ok_and(|t| t.is_socket()) {
+				out.push(Socket::Path(entry.path()));
+			}
+		}
+	}
+	out.sort_by_key(|s| s.to_string());
+	out
+}
+
+/// Send one command and wait for kitty's reply; `Ok` carries the reply's `data`.
+pub fn send(socket: &Socket, cmd: &str, payload: &Value) -> Result<Value> {
+	let mut stream = connect(socket)?;
+	stream.set_read_timeout(Some(TIMEOUT))?;
+	stream.set_write_timeout(Some(TIMEOUT))?;
+
+	let body = json!({ "cmd": cmd, "version": VERSION, "no_response": false, "payload": payload });
+	let mut message = PREFIX.to_vec();
+	message.extend_from_slice(body.to_string().as_bytes());
+	message.extend_from_slice(SUFFIX);
+	stream.write_all(&message).with_context(|| format!("send {} to {}", cmd, socket))?;
+
+	let mut reply = Vec::new();
+	let mut buf = [0u8; 4096];
+	while !reply.ends_with(SUFFIX) {
+		let n = stream.read(&mut buf).with_context(|| format!("no reply from {}", socket))?;
+		if n == 0 { break; }
+		reply.extend_from_slice(&buf[..n]);
+	}
+	let json = reply.strip_prefix(PREFIX).and_then(|r| r.strip_suffix(SUFFIX))
+		.ok_or_else(|| anyhow!("malformed reply from {}", socket))?;
+	let reply: Value = serde_json::from_slice(json).with_context(|| format!("malformed reply from {}", socket))?;
+	if reply.get("ok").and_then(Value::as_bool) == Some(true) {
+		return Ok(reply.get("data").cloned().unwrap_or(Value::Null));
+	}
+	let error = reply.get("error").and_then(Value::as_str).unwrap_or("unknown error");
+	Err(anyhow!("{} on {}: {}", cmd, socket, error.lines().next().unwrap_or(error)))
+}
+
+/// `send` to every socket at once (one thread each), results in socket order. The call returns
+/// after `TIMEOUT` at the latest: `UnixStream::connect` has no timeout of its own and blocks on
+/// an instance whose accept backlog is full, so such a socket is reported as not answering and
+/// its thread is left to finish in the background.
+pub fn broadcast(sockets: &[Socket], cmd: &str, payload: &Value) -> Vec<(Socket, Result<Value>)> {
+	let (tx, rx) = mpsc::channel();
+	for (i, socket) in sockets.iter().enumerate() {
+		let (tx, socket, cmd, payload) = (tx.clone(), socket.clone(), cmd.to_string(), payload.clone());
+		std::thread::spawn(move || { let _ = tx.send((i, send(&socket, &cmd, &payload))); });
+	}
+	drop(tx);
+
+	let deadline = Instant::now() + TIMEOUT;
+	let mut results: Vec<Option<Result<Value>>> = sockets.iter().map(|_| None).collect();
+	while results.iter().any(Option::is_none) {
+		match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+			Ok((i, result)) => results[i] = Some(result),
+			// Out of time, or every thread is done (a panicked one never sends).
+			Err(_) => break,
+		}
+	}
+	sockets.iter().cloned().zip(results).map(|(socket, result)| {
+		let result = result.unwrap_or_else(|| Err(anyhow!("{} did not answer within {:?}", socket, TIMEOUT)));
+		(socket, result)
+	}).collect()
+}
+
+/// `Ok` if at least one instance accepted; otherwise the first error.
+pub fn any_ok(results: Vec<(Socket, Result<Value>)>) -> Result<()> {
+	let mut first_error = None;
+	for (_, result) in results {
+		match result {
+			Ok(_) => return Ok(()),
+			Err(e) => { first_error.get_or_insert(e); }
+		}
+	}
+	Err(first_error.unwrap_or_else(|| anyhow!("no kitty sockets found")))
+}
+
+/// `set-colors` payload for every window (and new ones), from a kitty colors file
+/// (`name #RRGGBB` lines). Colors travel as 0xRRGGBB integers.
+pub fn set_colors_payload(colors_conf: &str) -> Value {
+	let mut colors = serde_json::Map::new();
+	for line in colors_conf.lines() {
+		let mut parts = line.split_whitespace();
+		let (Some(name), Some(value)) = (parts.next(), parts.next()) else { continue };
+		if name.starts_with('#') { continue; }
+		if let Some(hex) = value.strip_prefix('#') && hex.len() == 6 && let Ok(rgb) = u32::from_str_radix(hex, 16) {
+			colors.insert(name.to_string(), json!(rgb));
+		}
+	}
+	json!({ "all": true, "configured": true, "colors": colors })
+}
+
+fn connect(socket: &Socket) -> Result<UnixStream> {
+	match socket {
+		Socket::Path(path) => UnixStream::connect(path).with_context(|| format!("connect {}", socket)),
+		Socket::Abstract(name) => {
+			use std::os::linux::net::SocketAddrExt;
+			let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+			UnixStream::connect_addr(&addr).with_context(|| format!("connect {}", socket))
+		}
+	}
+}
@@ -0,0 +1,120 @@
+// Shared file plumbing for appliers that edit files the user also owns: a marked block that
+// is replaced on every run while everything outside it is left alone, in-place key edits for
+// INI/TOML sections, and atomic writes.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Begin/end marker lines, e.g. `/* >>> color-listener >>> */` for CSS.
+pub struct ManagedBlock {
+	pub begin: &'static str,
+	pub end: &'static str,
+}
+
+impl ManagedBlock {
+	/// `contents` with the block's body set to `body`. An existing block is replaced in place;
+	/// otherwise the block is prepended so later user rules can still override it.
+	pub fn replace(&self, contents: &str, body: &str) -> String {
+		let mut block = format!("{}\n{}", self.begin, body);
+		if !block.ends_with('\n') { block.push('\n'); }
+		block.push_str(self.end);
+		block.push('\n');
+
+		if let Some(start) = contents.find(self.begin)
+			&& let Some(end_rel) = contents[start..].find(self.end)
+		{
+			let mut end = start + end_rel + self.end.len();
+			if contents[end..].starts_with('\n') { end += 1; }
+			return format!("{}{}{}", &contents[..start], block, &contents[end..]);
+		}
+		if contents.is_empty() { return block; }
+		format!("{}\n{}", block, contents)
+	}
+
+	/// Read `path` (missing is fine), replace the block and write it back atomically.
+	pub fn write(&self, path: &Path, body: &str) -> Result<()> {
+		let contents = match fs::read_to_string(path) {
+			Ok(c) => c,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+			Err(e) => return Err(e).with_context(|| format!("read {}", path.display())),
+		};
+		write_atomic(path, &self.replace(&contents, body))
+	}
+}
+
+/// `contents` with `key = value` set inside `[section]` of an INI or TOML file. Existing keys keep
+/// their indentation and trailing `# comment`; missing keys go at the end of the section and a
+/// missing section is appended.
+pub fn set_section_keys(contents: &str, section: &str, values: &[(&str, String)]) -> String {
+	let header = format!("[{}]", section);
+	let mut out: Vec<String> = Vec::new();
+	let mut seen = vec![false; values.len()];
+	let mut in_section = false;
+	// Index in `out` after the section's last non-empty line, where missing keys are inserted.
+	let mut insert_at = None;
+	for line in contents.lines() {
+		let trimmed = line.trim();
+		if trimmed.starts_with('[') {
+			in_section = trimmed == header;
+			out.push(line.to_string());
+			if in_section { insert_at = Some(out.len()); }
+			continue;
+		}
+		if in_section {
+			let key = trimmed.split('=').next().unwrap_or("").trim();
+			if let Some(i) = values.iter().position(|(k, _)| *k == key) && trimmed.contains('=') {
+				out.push(replace_value(line, &values[i].1));
+				seen[i] = true;
+			} else {
+				out.push(line.to_string());
+			}
+			if !trimmed.is_empty() { insert_at = Some(out.len()); }
+		} else {
+			out.push(line.to_string());
+		}
+	}
+	let missing: Vec<String> = values.iter().zip(&seen).filter(|(_, s)| !**s).map(|((k, v), _)| format!("{} = {}", k, v)).collect();
+	match insert_at {
+		Some(at) => { out.splice(at..at, missing); }
+		None => {
+			if out.last().is_some_and(|l| !l.trim().is_empty()) { out.push(String::new()); }
+			out.push(header);
+			out.extend(missing);
+		}
+	}
+	let mut joined = out.join("\n");
+	joined.push('\n');
+	joined
+}
+
+// Swap the value after `=` but keep the spacing and a trailing comment. A `#` only starts a
+// comment outside quotes, so `fg = "#aabbcc"` stays intact.
+fn replace_value(line: &str, value: &str) -> String {
+	let eq = line.find('=').unwrap_or(line.len());
+	let rest = &line[eq + 1..];
+	let mut in_quotes = false;
+	let mut comment_at = rest.len();
+	for (i, c) in rest.char_indices() {
+		match c {
+			'"' => in_quotes = !in_quotes,
+			'#' if !in_quotes => { comment_at = i; break; }
+			_ => {}
+		}
+	}
+	let old = &rest[..comment_at];
+	let lead = &old[..old.len() - old.trim_start().len()];
+	let trail = if comment_at < rest.len() { &old[old.trim_end().len()..] } else { "" };
+	format!("{}={}{}{}{}", &line[..eq], if lead.is_empty() { " " } else { lead }, value, trail, &rest[comment_at..])
+}
+
+/// Write via `<file>.tmp` + rename, creating the parent directory if needed.
+pub fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+	}
+	let tmp = format!("{}.tmp", path.display());
+	fs::write(&tmp, contents).with_context(|| format!("write {}", tmp))?;
+	fs::rename(&tmp, path).with_context(|| format!("rename {} to {}", tmp, path.display()))?;
+	Ok(())
+}
@@ -1,4 +1,178 @@
 pub mod hyprland;
 pub mod kitty;
+pub mod kitty_rc;
 pub mod ags;
 pub mod ps1;
+pub mod gtk;
+pub mod custombar;
+pub mod eww;
+pub mod alacritty;
+pub mod foot;
+pub mod wezterm;
+pub mod fuzzel;
+pub mod yazi;
+pub mod fastfetch;
+pub mod managed;
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::palette::Palette;
+use crate::paths::expand_home;
+
+/// One setting an applier understands, e.g. `kitty.conf`.
+pub struct ConfigKey {
+    pub name: &'static str,
+    pub default: &'static str,
+    pub help: &'static str,
+}
+
+/// Raw `key -> value` settings for a single applier (CLI `--set`, legacy flags).
+pub type ApplierSettings = HashMap<String, String>;
+
+/// What an applier did; `warnings` are non-fatal problems such as a failed live reload.
+#[derive(Debug, Clone, Default)]
+pub struct ApplyReport {
+    pub summary: String,
+    pub warnings: Vec<String>,
+}
+
+impl ApplyReport {
+    pub fn new(summary: impl Into<String>) -> Self {
+        Self { summary: summary.into(), warnings: Vec::new() }
+    }
+
+    pub fn warn(mut self, warning: impl Into<String>) -> Self {
+        self.warnings.push(warning.into());
+        self
+    }
+}
+
+/// A target that turns a palette into config files / live reloads for one program.
+pub trait Applier: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn config_schema(&self) -> &'static [ConfigKey];
+    fn apply(&self, palette: &Palette) -> Result<ApplyReport>;
+
+    /// Files `apply` may write, snapshotted first so a failed theme switch can be rolled back.
+    fn targets(&self) -> Vec<PathBuf> { Vec::new() }
+
+    /// Make the program pick up its files again; called after a rollback restored them.
+    fn reload(&self) -> Result<()> { Ok(()) }
+}
+
+/// Setting every applier accepts on top of its schema: `required = false` lets a theme
+/// switch go through even when this applier fails; by default a failure rolls everything back.
+pub const REQUIRED_KEY: &str = "required";
+
+/// Strip `required` out of every applier's settings and return the names marked optional.
+pub fn take_optional(settings: &mut HashMap<String, ApplierSettings>) -> Result<Vec<String>> {
+    let mut optional = Vec::new();
+    for (name, values) in settings.iter_mut() {
+        match values.remove(REQUIRED_KEY).as_deref() {
+            None | Some("true") => {}
+            Some("false") => optional.push(name.clone()),
+            Some(other) => return Err(anyhow!("{}.{} must be true or false, got {}", name, REQUIRED_KEY, other)),
+        }
+    }
+    Ok(optional)
+}
+
+/// How the registry builds an applier from its settings.
+pub struct Registration {
+    pub name: &'static str,
+    pub schema: &'static [ConfigKey],
+    /// Run without being asked for; newer appliers are opt-in via `enabled = true` or `--appliers`.
+    pub default_enabled: bool,
+    pub build: fn(&Settings) -> Result<Box<dyn Applier>>,
+}
+
+/// Settings for one applier with schema defaults filled in.
+pub struct Settings<'a> {
+    applier: &'static str,
+    schema: &'static [ConfigKey],
+    values: Option<&'a ApplierSettings>,
+}
+
+impl Settings<'_> {
+    pub fn get(&self, key: &str) -> Result<String> {
+        if let Some(v) = self.values.and_then(|m| m.get(key)) { return Ok(v.clone()); }
+        self.schema.iter()
+            .find(|k| k.name == key)
+            .map(|k| k.default.to_string())
+            .ok_or_else(|| anyhow!("applier {} has no setting named {}", self.applier, key))
+    }
+
+    /// A setting holding a path, with `~/` and `$HOME/` expanded.
+    pub fn path(&self, key: &str) -> Result<PathBuf> {
+        Ok(expand_home(&self.get(key)?))
+    }
+
+    /// A yes/no setting: `true`/`false`, `yes`/`no` or `1`/`0`.
+    pub fn flag(&self, key: &str) -> Result<bool> {
+        match self.get(key)?.trim().to_lowercase().as_str() {
+            "true" | "yes" | "1" => Ok(true),
+            "false" | "no" | "0" => Ok(false),
+            other => Err(anyhow!("{}.{} must be true or false, got {}", self.applier, key, other)),
+        }
+    }
+}
+
+/// All known appliers. Adding a target means adding one `Registration` here.
+pub struct Registry {
+    entries: Vec<Registration>,
+}
+
+impl Registry {
+    pub fn builtin() -> Self {
+        Self { entries: vec![
+            ags::registration(),
+            hyprland::registration(),
+            kitty::registration(),
+            ps1::registration(),
+            gtk::registration(),
+            custombar::registration(),
+            eww::registration(),
+            alacritty::registration(),
+            foot::registration(),
+            wezterm::registration(),
+            fuzzel::registration(),
+            yazi::registration(),
+            fastfetch::registration(),
+        ] }
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.entries.iter().map(|e| e.name).collect()
+    }
+
+    /// Appliers that run when neither the config nor `--appliers` says otherwise.
+    pub fn default_names(&self) -> Vec<&'static str> {
+        self.entries.iter().filter(|e| e.default_enabled).map(|e| e.name).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Registration> {
+        let name = name.to_lowercase();
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// Build the named appliers (in order). Unknown names and unknown settings keys are errors.
+    pub fn resolve(&self, names: &[String], settings: &HashMap<String, ApplierSettings>) -> Result<Vec<Box<dyn Applier>>> {
+        for (applier, values) in settings {
+            let reg = self.get(applier)
+                .ok_or_else(|| anyhow!("settings given for unknown applier {} (known: {})", applier, self.names().join(", ")))?;
+            for key in values.keys() {
+                if !reg.schema.iter().any(|k| k.name == key) {
+                    let known: Vec<&str> = reg.schema.iter().map(|k| k.name).collect();
+                    return Err(anyhow!("unknown setting {}.{} (known: {})", applier, key, known.join(", ")));
+                }
+            }
+        }
+        names.iter().map(|name| {
+            let reg = self.get(name)
+                .ok_or_else(|| anyhow!("unknown applier {} (known: {})", name, self.names().join(", ")))?;
+            (reg.build)(&Settings { applier: reg.name, schema: reg.schema, values: settings.get(reg.name) })
+        }).collect()
+    }
+}
@@ -3,8 +3,10 @@ PS1 applier (concise)
 
 - Writes ~/.config/dotfiles/shell-colors.sh exporting TrueColor ANSI variables.
 - Provides Bash-bracketed and RAW variants; picks based on $BASH_VERSION at source time.
-- On update, broadcasts to Kitty to source the snippet and call __cl_ps1_reload (if present), then soft-clears via `kitty @ action clear_terminal scroll active`.
-- Busy shells aren’t interrupted; your prompt hook should re-source on each prompt.
+- zsh gets shell-colors.zsh (%F{#hex} on zsh 5.7+, %{...%}-wrapped escapes before that), fish gets shell-colors.fish (set_color).
+- Which shells: the `shells` setting; `auto` is bash plus $SHELL when that is zsh or fish.
+- Nothing is sent to running terminals: each file is replaced by a rename, and the prompt hook from notes.md notices the new file (inode and mtime) before drawing the next prompt, re-sources it and calls __cl_ps1_reload.
+- Editors, pagers and REPLs running in a shell never see any input; the shell picks the colors up once they exit.
 
 For full setup snippets, Home Manager notes, Kitty requirements, and tips, see notes.md in this folder.
 */
@@ -12,36 +14,117 @@ For full setup snippets, Home Manager notes, Kitty requirements, and tips, see n
 use anyhow::{anyhow, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::io::Read;
-use std::process::{Command, Stdio};
-#[cfg(unix)]
-use std::os::unix::fs::FileTypeExt;
+
+use super::{Applier, ApplyReport, ConfigKey, Registration};
+use crate::color::hex_to_rgb;
+use crate::palette::Palette;
+
+const SCHEMA: &[ConfigKey] = &[
+    ConfigKey { name: "rc", default: "~/.bashrc", help: "shell rc file (informational; rc files are never edited)" },
+    ConfigKey { name: "shells", default: "auto", help: "comma-separated bash, zsh, fish; auto = bash plus $SHELL" },
+];
+
+pub fn registration() -> Registration {
+    Registration {
+        name: "ps1",
+        schema: SCHEMA,
+        default_enabled: true,
+        build: |s| Ok(Box::new(Ps1Applier { rc: s.path("rc")?, shells: parse_shells(&s.get("shells")?)? })),
+    }
+}
+
+pub struct Ps1Applier {
+    rc: PathBuf,
+    shells: Vec<Shell>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Shell::Bash => "shell-colors.sh",
+            Shell::Zsh => "shell-colors.zsh",
+            Shell::Fish => "shell-colors.fish",
+        }
+    }
+
+    fn render(self, palette: &Palette) -> String {
+        match self {
+            Shell::Bash => render_color_vars(palette),
+            Shell::Zsh => render_zsh_vars(palette),
+            Shell::Fish => render_fish_vars(palette),
+        }
+    }
+
+}
+
+fn parse_shells(value: &str) -> Result<Vec<Shell>> {
+    if value.trim() == "auto" {
+        let mut shells = vec![Shell::Bash];
+        let login = std::env::var("SHELL").unwrap_or_default();
+        let login = login.rsplit('/').next().and_then(Shell::parse);
+        if let Some(shell) = login && shell != Shell::Bash { shells.push(shell); }
+        return Ok(shells);
+    }
+    value.split(',').filter(|n| !n.trim().is_empty()).map(|n| {
+        Shell::parse(n).ok_or_else(|| anyhow!("unknown shell {} for ps1.shells (known: bash, zsh, fish, auto)", n.trim()))
+    }).collect()
+}
+
+impl Applier for Ps1Applier {
+    fn name(&self) -> &'static str { "ps1" }
+    fn config_schema(&self) -> &'static [ConfigKey] { SCHEMA }
+
+    fn apply(&self, palette: &Palette) -> Result<ApplyReport> {
+        let mut written = Vec::new();
+        for shell in &self.shells {
+            written.push(write_shell_colors(*shell, palette)?.display().to_string());
+        }
+        // Running shells are left alone; their prompt hook re-sources the file before the next prompt.
+        Ok(ApplyReport::new(format!("updated PS1 theme {} (loader in {}; picked up at the next prompt)", written.join(", "), self.rc.display())))
+    }
+
+    fn targets(&self) -> Vec<PathBuf> {
+        self.shells.iter().filter_map(|s| shell_colors_path(*s).ok()).collect()
+    }
+}
 
 // Strategy for Home Manager compatibility:
 // - Generate a shell snippet with exported variables (TrueColor ANSI escapes),
 //   e.g. ~/.config/dotfiles/shell-colors.sh
-// - Home Manager (programs.bash.initExtra) will source this on each prompt via
-//   PROMPT_COMMAND. We do NOT touch rc files here.
+// - Home Manager (programs.bash.initExtra) installs a PROMPT_COMMAND hook that
+//   re-sources it whenever it was replaced. We do NOT touch rc files here.
 
-pub fn apply(_rc_path: &str, primary_hex: &str, secondary_hex: &str) -> Result<()> {
-    let colors_path = shell_colors_path()?;
+fn write_shell_colors(shell: Shell, palette: &Palette) -> Result<PathBuf> {
+    let colors_path = shell_colors_path(shell)?;
     if let Some(parent) = colors_path.parent() { fs::create_dir_all(parent)?; }
-    let snippet = render_color_vars(primary_hex, secondary_hex);
-    let tmp = colors_path.with_extension("sh.tmp");
+    let snippet = shell.render(palette);
+    let tmp = PathBuf::from(format!("{}.tmp", colors_path.display()));
     fs::write(&tmp, snippet)?;
     fs::rename(&tmp, &colors_path)?;
-
-    // Best-effort: ask all existing Kitty terminals to source theme and rebuild PS1 (soft-clear) in-place
-    if let Err(e) = reload_all_kitty_shells() {
-        eprintln!("[applier:ps1] warn: failed to trigger shell reloads via kitty: {e}");
-    }
-    Ok(())
+    Ok(colors_path)
 }
 
-fn render_color_vars(_primary_hex: &str, secondary_hex: &str) -> String {
-    // Use secondary for main PS1 colors; derive a slightly darker variant for dim sections.
+fn render_color_vars(palette: &Palette) -> String {
+    // Use the accent for main PS1 colors and the palette's dim accent for dim sections.
+    let secondary_hex = palette.secondary();
     let (sr, sg, sb) = hex_to_rgb(secondary_hex).unwrap_or((255, 255, 255));
-    let (dr, dg, db) = darken_rgb(sr, sg, sb, 0.18);
+    let (dr, dg, db) = hex_to_rgb(&palette.accent_variants.dim).unwrap_or((sr, sg, sb));
     // Bash uses \[...\] to mark non-printing; other shells shouldn't see those.
     let sfg_bash = format!("\\[\\e[38;2;{};{};{}m\\]", sr, sg, sb); // bright accent
     let dfg_bash = format!("\\[\\e[38;2;{};{};{}m\\]", dr, dg, db); // slightly darker accent
@@ -56,7 +139,7 @@ fn render_color_vars(_primary_hex: &str, secondary_hex: &str) -> String {
     let dbg_raw = format!("$'\\e[48;2;{};{};{}m'", dr, dg, db);
     let reset_raw = "$'\\e[0m'".to_string();
     format!(
-    "# Generated by color-listener\n# secondary = {s}\n# CL_FG_PRIMARY = darker(secondary), CL_FG_SECONDARY = secondary\n# Provide both Bash-bracketed and RAW ANSI variants; pick at runtime\n# Bash-bracketed (non-printing markers)\nexport CL_FG_PRIMARY_BASH=\"{dfg_bash}\"\nexport CL_FG_SECONDARY_BASH=\"{sfg_bash}\"\nexport CL_BG_PRIMARY_BASH=\"{dbg_bash}\"\nexport CL_BG_SECONDARY_BASH=\"{sbg_bash}\"\nexport CL_RESET_BASH=\"{reset_bash}\"\n# Raw ANSI (no markers)\nexport CL_FG_PRIMARY_RAW={dfg_raw}\nexport CL_FG_SECONDARY_RAW={sfg_raw}\nexport CL_BG_PRIMARY_RAW={dbg_raw}\nexport CL_BG_SECONDARY_RAW={sbg_raw}\nexport CL_RESET_RAW={reset_raw}\n# Select appropriate set\nif [ -n \"$BASH_VERSION\" ]; then\n  export CL_FG_PRIMARY=\"$CL_FG_PRIMARY_BASH\"\n  export CL_FG_SECONDARY=\"$CL_FG_SECONDARY_BASH\"\n  export CL_BG_PRIMARY=\"$CL_BG_PRIMARY_BASH\"\n  export CL_BG_SECONDARY=\"$CL_BG_SECONDARY_BASH\"\n  export CL_RESET=\"$CL_RESET_BASH\"\nelse\n  export CL_FG_PRIMARY=$CL_FG_PRIMARY_RAW\n  export CL_FG_SECONDARY=$CL_FG_SECONDARY_RAW\n  export CL_BG_PRIMARY=$CL_BG_PRIMARY_RAW\n  export CL_BG_SECONDARY=$CL_BG_SECONDARY_RAW\n  export CL_RESET=$CL_RESET_RAW\nfi\n",
+    "# Generated by color-listener\n# secondary = {s}\n# CL_FG_PRIMARY = accent-dim, CL_FG_SECONDARY = secondary\n# Provide both Bash-bracketed and RAW ANSI variants; pick at runtime\n# Bash-bracketed (non-printing markers)\nexport CL_FG_PRIMARY_BASH=\"{dfg_bash}\"\nexport CL_FG_SECONDARY_BASH=\"{sfg_bash}\"\nexport CL_BG_PRIMARY_BASH=\"{dbg_bash}\"\nexport CL_BG_SECONDARY_BASH=\"{sbg_bash}\"\nexport CL_RESET_BASH=\"{reset_bash}\"\n# Raw ANSI (no markers)\nexport CL_FG_PRIMARY_RAW={dfg_raw}\nexport CL_FG_SECONDARY_RAW={sfg_raw}\nexport CL_BG_PRIMARY_RAW={dbg_raw}\nexport CL_BG_SECONDARY_RAW={sbg_raw}\nexport CL_RESET_RAW={reset_raw}\n# Select appropriate set\nif [ -n \"$BASH_VERSION\" ]; then\n  export CL_FG_PRIMARY=\"$CL_FG_PRIMARY_BASH\"\n  export CL_FG_SECONDARY=\"$CL_FG_SECONDARY_BASH\"\n  export CL_BG_PRIMARY=\"$CL_BG_PRIMARY_BASH\"\n  export CL_BG_SECONDARY=\"$CL_BG_SECONDARY_BASH\"\n  export CL_RESET=\"$CL_RESET_BASH\"\nelse\n  export CL_FG_PRIMARY=$CL_FG_PRIMARY_RAW\n  export CL_FG_SECONDARY=$CL_FG_SECONDARY_RAW\n  export CL_BG_PRIMARY=$CL_BG_PRIMARY_RAW\n  export CL_BG_SECONDARY=$CL_BG_SECONDARY_RAW\n  export CL_RESET=$CL_RESET_RAW\nfi\n",
         s = secondary_hex,
         dfg_bash = dfg_bash,
         sfg_bash = sfg_bash,
@@ -71,143 +154,36 @@ fn render_color_vars(_primary_hex: &str, secondary_hex: &str) -> String {
     )
 }
 
-fn hex_to_rgb(hex: &str) -> Option<(u8, u8, u8)> {
-    if hex.len() == 7 && hex.starts_with('#') {
-        let r = u8::from_str_radix(&hex[1..3], 16).ok()?;
-        let g = u8::from_str_radix(&hex[3..5], 16).ok()?;
-        let b = u8::from_str_radix(&hex[5..7], 16).ok()?;
-        Some((r, g, b))
-    } else {
-        None
-    }
-}
-
-// queued re-exec removed by request; prompt hook should rebuild PS1 every time instead
-
-fn shell_colors_path() -> Result<PathBuf> {
-    let home = std::env::var("HOME").map_err(|e| anyhow!("HOME not set: {e}"))?;
-    Ok(Path::new(&home).join(".config/dotfiles/shell-colors.sh"))
-}
-
-fn darken_rgb(r: u8, g: u8, b: u8, amount: f32) -> (u8, u8, u8) {
-    let clamp = |v: f32| -> u8 { v.round().clamp(0.0, 255.0) as u8 };
-    (
-        clamp(r as f32 * (1.0 - amount)),
-        clamp(g as f32 * (1.0 - amount)),
-        clamp(b as f32 * (1.0 - amount)),
+// Same roles as the bash snippet (CL_FG_PRIMARY = accent-dim, CL_FG_SECONDARY = accent) so a
+// prompt ported from bash keeps its colors. zsh 5.7 added %F{#hex}; older versions get the raw
+// escapes wrapped in %{...%} so the line editor does not count them as printing.
+fn render_zsh_vars(palette: &Palette) -> String {
+    let secondary_hex = palette.secondary();
+    let dim_hex = palette.accent_variants.dim.as_str();
+    let (sr, sg, sb) = hex_to_rgb(secondary_hex).unwrap_or((255, 255, 255));
+    let (dr, dg, db) = hex_to_rgb(dim_hex).unwrap_or((sr, sg, sb));
+    format!(
+    "# Generated by color-listener\n# secondary = {s}\n# CL_FG_PRIMARY = accent-dim, CL_FG_SECONDARY = secondary\n# %F/%K with hex colors (zsh 5.7+)\nCL_FG_PRIMARY_HEX='%F{{{d}}}'\nCL_FG_SECONDARY_HEX='%F{{{s}}}'\nCL_BG_PRIMARY_HEX='%K{{{d}}}'\nCL_BG_SECONDARY_HEX='%K{{{s}}}'\nCL_RESET_HEX='%f%k'\n# Raw ANSI inside %{{...%}} (non-printing markers)\nCL_FG_PRIMARY_ESC=$'%{{\\e[38;2;{dr};{dg};{db}m%}}'\nCL_FG_SECONDARY_ESC=$'%{{\\e[38;2;{sr};{sg};{sb}m%}}'\nCL_BG_PRIMARY_ESC=$'%{{\\e[48;2;{dr};{dg};{db}m%}}'\nCL_BG_SECONDARY_ESC=$'%{{\\e[48;2;{sr};{sg};{sb}m%}}'\nCL_RESET_ESC=$'%{{\\e[0m%}}'\n# Select appropriate set\nautoload -Uz is-at-least\nif is-at-least 5.7; then\n  CL_FG_PRIMARY=$CL_FG_PRIMARY_HEX\n  CL_FG_SECONDARY=$CL_FG_SECONDARY_HEX\n  CL_BG_PRIMARY=$CL_BG_PRIMARY_HEX\n  CL_BG_SECONDARY=$CL_BG_SECONDARY_HEX\n  CL_RESET=$CL_RESET_HEX\nelse\n  CL_FG_PRIMARY=$CL_FG_PRIMARY_ESC\n  CL_FG_SECONDARY=$CL_FG_SECONDARY_ESC\n  CL_BG_PRIMARY=$CL_BG_PRIMARY_ESC\n  CL_BG_SECONDARY=$CL_BG_SECONDARY_ESC\n  CL_RESET=$CL_RESET_ESC\nfi\n",
+        s = secondary_hex,
+        d = dim_hex,
     )
 }
 
-// ---- Kitty helpers (local copy, minimal) ----
-
-fn reload_all_kitty_shells() -> Result<()> {
-    // Locate kitty.conf to read listen_on; prefer dotfiles kitty path used by this project
-    let mut targets: Vec<String> = Vec::new();
-    if let Some(base) = read_kitty_listen_on() {
-        // expand per-PID variants
-        if let Some(paths) = discover_unix_socket_variants(&base) {
-            for p in paths { targets.push(p); }
-        } else {
-            targets.push(base);
-        }
-    }
-
-    // Fallback: try without explicit --to (may only hit one instance)
-    if targets.is_empty() {
-        // Reload prompt first, then soft clear
-        send_exec_to_kitty(None)?;
-        let _ = soft_clear_kitty(None);
-        return Ok(());
-    }
-
-    // Send to each discovered instance; ignore individual failures
-    let mut any = false;
-    for to in targets {
-        // Reload prompt first
-        if send_exec_to_kitty(Some(&to)).is_ok() { any = true; }
-        // Then soft clear (non-fatal if it fails)
-        let _ = soft_clear_kitty(Some(&to));
-    }
-    if any { Ok(()) } else { Err(anyhow!("no kitty instances accepted send-text")) }
-}
-
-fn send_exec_to_kitty(to: Option<&str>) -> Result<()> {
-    // Use --stdin to ensure a trailing newline is handled correctly
-    let mut cmd = Command::new("kitty");
-    cmd.arg("@");
-    if let Some(t) = to { cmd.args(["--to", t]); }
-    cmd.args(["send-text", "--all", "--stdin"]).stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null());
-    let mut child = cmd.spawn()?;
-    if let Some(mut stdin) = child.stdin.take() {
-        use std::io::Write as _;
-        // Source color snippet and rebuild PS1 immediately (no exec). No ESC-based clears here.
-        let payload = b"CL_PS1_THEME=\"$HOME/.config/dotfiles/shell-colors.sh\"; [[ -f \"$CL_PS1_THEME\" ]] && . \"$CL_PS1_THEME\"; type __cl_ps1_reload >/dev/null 2>&1 && __cl_ps1_reload\n";
-        stdin.write_all(payload)?;
-    }
-    let status = child.wait()?;
-    if status.success() { Ok(()) } else { Err(anyhow!("kitty send-text failed")) }
-}
-
-fn soft_clear_kitty(to: Option<&str>) -> Result<()> {
-    use std::process::Command;
-    let mut cmd = Command::new("kitty");
-    cmd.arg("@");
-    if let Some(t) = to { cmd.args(["--to", t]); }
-    cmd.args(["action", "clear_terminal", "scroll", "active"]).stdout(Stdio::null()).stderr(Stdio::null());
-    let status = cmd.status()?;
-    if status.success() { Ok(()) } else { Err(anyhow!("kitty clear_terminal failed")) }
+// fish prompts print colors instead of embedding markers, so the snippet runs set_color once
+// and keeps the resulting escapes; the bare hex values are there for `set_color $CL_..._HEX`.
+fn render_fish_vars(palette: &Palette) -> String {
+    let secondary = palette.secondary().trim_start_matches('#');
+    let dim = palette.accent_variants.dim.trim_start_matches('#');
+    format!(
+    "# Generated by color-listener\n# secondary = #{s}\n# CL_FG_PRIMARY = accent-dim, CL_FG_SECONDARY = secondary\nset -g CL_PRIMARY_HEX {d}\nset -g CL_SECONDARY_HEX {s}\nset -g CL_FG_PRIMARY (set_color $CL_PRIMARY_HEX)\nset -g CL_FG_SECONDARY (set_color $CL_SECONDARY_HEX)\nset -g CL_BG_PRIMARY (set_color -b $CL_PRIMARY_HEX)\nset -g CL_BG_SECONDARY (set_color -b $CL_SECONDARY_HEX)\nset -g CL_RESET (set_color normal)\n",
+        s = secondary,
+        d = dim,
+    )
 }
 
-fn read_kitty_listen_on() -> Option<String> {
-    // Try common locations; prefer repo's dotfiles path
-    let mut candidates: Vec<PathBuf> = Vec::new();
-    if let Ok(home) = std::env::var("HOME") {
-        candidates.push(Path::new(&home).join(".config/dotfiles/kitty/kitty.conf"));
-        candidates.push(Path::new(&home).join(".config/kitty/kitty.conf"));
-    }
-    for p in candidates {
-        if p.exists() {
-            let mut s = String::new();
-            if fs::File::open(&p).and_then(|mut f| f.read_to_string(&mut s)).is_ok() {
-                for line in s.lines() {
-                    let t = line.trim();
-                    if let Some(rest) = t.strip_prefix("listen_on ") {
-                        let v = rest.trim();
-                        if !v.is_empty() { return Some(v.to_string()); }
-                    }
-                }
-            }
-        }
-    }
-    None
-}
+// queued re-exec removed by request; prompt hook should rebuild PS1 every time instead
 
-// Discover all socket files that start with the base name (kitty appends -$PID)
-fn discover_unix_socket_variants(target: &str) -> Option<Vec<String>> {
-    let prefix = "unix:";
-    if !target.starts_with(prefix) { return None; }
-    let path = &target[prefix.len()..];
-    if !path.starts_with('/') { return None; }
-    let p = std::path::Path::new(path);
-    let parent = p.parent()?;
-    let fname = p.file_name()?.to_string_lossy();
-    let base_prefix = fname.split_once('-').map(|(b, _)| b).unwrap_or(&fname);
-    let mut out = Vec::new();
-    if let Ok(read) = fs::read_dir(parent) {
-        for entry in read.flatten() {
-            let name = entry.file_name();
-            let name = name.to_string_lossy();
-            if name.starts_with(base_prefix) {
-                if let Ok(ft) = entry.file_type() {
-                    #[cfg(unix)]
-                    {
-                        if ft.is_socket() {
-                            out.push(format!("unix:{}", entry.path().to_string_lossy()));
-                        }
-                    }
-                }
-            }
-        }
-    }
-    if out.is_empty() { None } else { Some(out) }
+fn shell_colors_path(shell: Shell) -> Result<PathBuf> {
+    let home = std::env::var("HOME").map_err(|e| anyhow!("HOME not set: {e}"))?;
+    Ok(Path::new(&home).join(".config/dotfiles").join(shell.file_name()))
 }
@@ -0,0 +1,79 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::managed::write_atomic;
+use super::{Applier, ApplyReport, ConfigKey, Registration};
+use crate::palette::Palette;
+
+const SCHEMA: &[ConfigKey] = &[
+	ConfigKey { name: "scheme", default: "~/.config/wezterm/colors/color-listener.toml", help: "generated color scheme (select it with config.color_scheme)" },
+	ConfigKey { name: "config", default: "~/.config/wezterm/wezterm.lua", help: "wezterm.lua to touch so running windows reload (empty to skip)" },
+];
+
+pub fn registration() -> Registration {
+	Registration {
+		name: "wezterm",
+		schema: SCHEMA,
+		default_enabled: false,
+		build: |s| {
+			let config = if s.get("config")?.trim().is_empty() { None } else { Some(s.path("config")?) };
+			Ok(Box::new(WeztermApplier { scheme: s.path("scheme")?, config }))
+		},
+	}
+}
+
+pub struct WeztermApplier {
+	scheme: PathBuf,
+	config: Option<PathBuf>,
+}
+
+impl Applier for WeztermApplier {
+	fn name(&self) -> &'static str { "wezterm" }
+	fn config_schema(&self) -> &'static [ConfigKey] { SCHEMA }
+
+	fn apply(&self, palette: &Palette) -> Result<ApplyReport> {
+		write_atomic(&self.scheme, &render_scheme(palette, &scheme_name(&self.scheme)))?;
+		let report = ApplyReport::new(format!("updated {}", self.scheme.display()));
+		if let Err(e) = self.reload() {
+			return Ok(report.warn(format!("wezterm not reloaded: {e}")));
+		}
+		Ok(report)
+	}
+
+	fn targets(&self) -> Vec<PathBuf> { vec![self.scheme.clone()] }
+
+	// wezterm reloads when its config file changes (automatically_reload_config, on by default)
+	// but does not watch the colors dir, so bump the config's mtime.
+	fn reload(&self) -> Result<()> {
+		let Some(config) = &self.config else { return Ok(()) };
+		touch(config)
+	}
+}
+
+// The scheme name is the file stem, e.g. `color-listener` for colors/color-listener.toml.
+fn scheme_name(path: &Path) -> String {
+	path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "color-listener".to_string())
+}
+
+fn render_scheme(palette: &Palette, name: &str) -> String {
+	let quoted = |colors: &[String]| colors.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>().join(", ");
+	format!(
+		"# Color scheme (managed by color-listener)\n[colors]\nbackground = \"{bg}\"\nforeground = \"{fg}\"\ncursor_bg = \"{accent}\"\ncursor_fg = \"{bg}\"\ncursor_border = \"{accent}\"\nselection_bg = \"{sel}\"\nselection_fg = \"{fg}\"\nansi = [{ansi}]\nbrights = [{brights}]\n\n[metadata]\nname = \"{name}\"\n",
+		bg = palette.background,
+		fg = palette.foreground,
+		accent = palette.accent,
+		sel = palette.accent_variants.dim,
+		ansi = quoted(&palette.ansi[..8]),
+		brights = quoted(&palette.ansi[8..]),
+		name = name,
+	)
+}
+
+fn touch(path: &Path) -> Result<()> {
+	let file = fs::File::options().write(true).open(path)
+		.map_err(|e| anyhow!("open {}: {}", path.display(), e))?;
+	file.set_modified(SystemTime::now())?;
+	Ok(())
+}
@@ -0,0 +1,75 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use super::managed::{set_section_keys, write_atomic};
+use super::{Applier, ApplyReport, ConfigKey, Registration};
+use crate::palette::Palette;
+
+const SCHEMA: &[ConfigKey] = &[
+	ConfigKey { name: "theme", default: "~/.config/dotfiles/yazi/theme.toml", help: "yazi theme.toml whose accent keys are overridden (created, with a warning, if missing)" },
+];
+
+pub fn registration() -> Registration {
+	Registration { name: "yazi", schema: SCHEMA, default_enabled: false, build: |s| Ok(Box::new(YaziApplier { theme: s.path("theme")? })) }
+}
+
+pub struct YaziApplier {
+	theme: PathBuf,
+}
+
+impl Applier for YaziApplier {
+	fn name(&self) -> &'static str { "yazi" }
+	fn config_schema(&self) -> &'static [ConfigKey] { SCHEMA }
+
+	fn apply(&self, palette: &Palette) -> Result<ApplyReport> {
+		let (mut contents, created) = match fs::read_to_string(&self.theme) {
+			Ok(c) => (c, false),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => (String::new(), true),
+			Err(e) => return Err(anyhow!("read {}: {}", self.theme.display(), e)),
+		};
+		for (section, values) in sections(palette) {
+			contents = set_section_keys(&contents, section, &values);
+		}
+		write_atomic(&self.theme, &contents)?;
+		let report = ApplyReport::new(format!("updated {} (applies to new yazi instances)", self.theme.display()));
+		if created {
+			let keys: Vec<&str> = sections(palette).into_iter().map(|(section, _)| section).collect();
+			return Ok(report.warn(format!(
+				"{} did not exist; created it with only the [{}] accent keys, everything else still comes from yazi's flavor",
+				self.theme.display(),
+				keys.join("], ["),
+			)));
+		}
+		Ok(report)
+	}
+
+	fn targets(&self) -> Vec<PathBuf> { vec![self.theme.clone()] }
+}
+
+// The only keys this applier owns. yazi layers theme.toml over the active flavor key by key, so
+// file type colors, icons, tabs and the status bar stay with the flavor or the user.
+fn sections(palette: &Palette) -> Vec<(&'static str, Vec<(&'static str, String)>)> {
+	let fg = |hex: &str| format!("{{ fg = \"{}\" }}", hex);
+	let main = |hex: &str| format!("{{ fg = \"{}\", bg = \"{}\", bold = true }}", palette.background, hex);
+	let alt = |hex: &str| format!("{{ fg = \"{}\", bg = \"{}\" }}", hex, palette.surface);
+	vec![
+		("mgr", vec![
+			("cwd", fg(&palette.accent)),
+			("border_style", fg(&palette.surface)),
+			("marker_selected", format!("{{ fg = \"{a}\", bg = \"{a}\" }}", a = palette.accent)),
+		]),
+		("mode", vec![
+			("normal_main", main(&palette.accent)),
+			("normal_alt", alt(&palette.accent)),
+			("select_main", main(&palette.ansi[2])),
+			("select_alt", alt(&palette.ansi[2])),
+			("unset_main", main(&palette.ansi[1])),
+			("unset_alt", alt(&palette.ansi[1])),
+		]),
+		("input", vec![
+			("border", fg(&palette.accent)),
+			("title", fg(&palette.foreground)),
+		]),
+	]
+}
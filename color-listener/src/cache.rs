@@ -0,0 +1,141 @@
+// On-disk palette cache: `$XDG_CACHE_HOME/color-listener/palettes/<hash>-<profile>.json`
+// (`<hash>-<profile>-<extraction>.json` for the clustering extractions).
+// Keyed by the wallpaper's file contents rather than its path, so renamed or re-downloaded
+// files still hit and an edited file under the same name misses.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::palette::Palette;
+use crate::profile::{Extraction, Profile};
+
+/// Bump whenever extraction changes enough that old entries would be wrong.
+const CACHE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub version: u32,
+    /// Where the wallpaper was when the entry was written; informational only.
+    pub wallpaper: PathBuf,
+    pub profile: String,
+    pub palette: Palette,
+}
+
+pub struct PaletteCache {
+    dir: PathBuf,
+}
+
+impl PaletteCache {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self { dir: dir.as_ref().to_path_buf() }
+    }
+
+    pub fn default_dir() -> PathBuf {
+        if let Some(xdg) = std::env::var_os("XDG_CACHE_HOME") { return PathBuf::from(xdg).join("color-listener/palettes"); }
+        let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+        home.join(".cache/color-listener/palettes")
+    }
+
+    pub fn dir(&self) -> &Path { &self.dir }
+
+    /// Composites of several monitors' wallpapers (hyprpaper `monitor = blend`), next to the palettes.
+    pub fn blends_dir(&self) -> PathBuf { self.dir.with_file_name("blends") }
+
+    /// Cache key for `wallpaper` under `profile`: content hash of the file plus the profile name,
+    /// and the extraction when it is not the histogram (so existing keys stay valid).
+    pub fn key(wallpaper: &Path, profile: &Profile) -> Result<String> {
+        let bytes = fs::read(wallpaper).with_context(|| format!("read {}", wallpaper.display()))?;
+        let mut key = format!("{:016x}-{}", fnv1a64(&bytes), profile.name);
+        if profile.extraction != Extraction::Histogram {
+            key.push_str(&format!("-{}", profile.extraction));
+        }
+        Ok(key)
+    }
+
+    /// Stored palette for `key`, if any. Unreadable or outdated entries count as misses.
+    pub fn get(&self, key: &str) -> Option<Palette> {
+        let text = fs::read_to_string(self.entry_path(key)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&text).ok()?;
+        (entry.version == CACHE_VERSION).then_some(entry.palette)
+    }
+
+    pub fn put(&self, key: &str, wallpaper: &Path, profile: &Profile, palette: &Palette) -> Result<()> {
+        fs::create_dir_all(&self.dir).with_context(|| format!("create {}", self.dir.display()))?;
+        let entry = CacheEntry {
+            version: CACHE_VERSION,
+            wallpaper: wallpaper.to_path_buf(),
+            profile: profile.name.to_string(),
+            palette: palette.clone(),
+        };
+        let path = self.entry_path(key);
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&entry)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Every readable entry with its key and write time, newest first.
+    pub fn list(&self) -> Result<Vec<(String, SystemTime, CacheEntry)>> {
+        let read = match fs::read_dir(&self.dir) {
+            Ok(r) => r,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(anyhow!("read {}: {}", self.dir.display(), e)),
+        };
+        let mut out = Vec::new();
+        for entry in read.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|e| e != "json") { continue; }
+            let Some(key) = path.file_stem().map(|s| s.to_string_lossy().into_owned()) else { continue };
+            let Ok(text) = fs::read_to_string(&path) else { continue };
+            let Ok(parsed) = serde_json::from_str::<CacheEntry>(&text) else { continue };
+            let mtime = entry.metadata().and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+            out.push((key, mtime, parsed));
+        }
+        out.sort_by_key(|e| std::cmp::Reverse(e.1));
+        Ok(out)
+    }
+
+    /// Remove every entry; returns how many files were deleted.
+    pub fn clear(&self) -> Result<usize> {
+        remove_files(&self.dir, &["json", "tmp"])
+    }
+
+    /// Remove every blended wallpaper; returns how many files were deleted.
+    pub fn clear_blends(&self) -> Result<usize> {
+        remove_files(&self.blends_dir(), &["png"])
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+fn remove_files(dir: &Path, extensions: &[&str]) -> Result<usize> {
+    let read = match fs::read_dir(dir) {
+        Ok(r) => r,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(anyhow!("read {}: {}", dir.display(), e)),
+    };
+    let mut removed = 0;
+    for entry in read.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|e| extensions.iter().any(|x| e == *x)) {
+            fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// FNV-1a: stable across builds and Rust versions, unlike `DefaultHasher`.
+pub(crate) fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
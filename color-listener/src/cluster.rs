@@ -0,0 +1,225 @@
+// Clustering extractions for busy wallpapers: group the thumbnail's pixels in OkLab (k-means or
+// median cut) and take the hues of the strongest colorful clusters, so several distinct accents
+// come out instead of the histogram's single dominant hue. Both are deterministic: median cut by
+// construction, k-means through a fixed seed.
+
+use image::DynamicImage;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+use crate::color::{hue_delta, srgb8_to_oklab};
+use crate::profile::Extraction;
+
+const MAX_THUMB: u32 = 256;
+/// Clusters to split the image into; only the colorful ones become accents.
+const K: usize = 8;
+const KMEANS_ITERATIONS: usize = 16;
+/// Same image, same clusters, same palette.
+const SEED: u64 = 0x5EED_C0102;
+const CENTER_SIGMA_FRAC: f32 = 0.38;
+
+/// Clusters below this chroma are neutrals (sky, concrete, shadows) and never an accent.
+const MIN_C: f32 = 0.030;
+const C_WEIGHT_GAMMA: f32 = 1.25;
+/// Accents closer than this in hue would read as the same color.
+const MIN_ACCENT_SEPARATION_DEG: f32 = 30.0;
+/// A cluster needs this share of the colorful weight to become an accent.
+const MIN_ACCENT_SHARE: f32 = 0.05;
+pub const MAX_ACCENTS: usize = 4;
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    lab: [f32; 3],
+    weight: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Cluster {
+    lab: [f32; 3],
+    weight: f32,
+}
+
+/// (hue in radians, chroma) of up to `MAX_ACCENTS` distinct accents, strongest first. Empty when
+/// the image has no colorful cluster (or `method` is the histogram, which works elsewhere).
+pub fn accent_hues(img: &DynamicImage, method: Extraction) -> Vec<(f32, f32)> {
+    let clusters = match method {
+        Extraction::Histogram => return Vec::new(),
+        Extraction::KMeans => kmeans(&samples(img)),
+        Extraction::MedianCut => median_cut(&samples(img)),
+    };
+
+    // Rank like the histogram does: focal weight times chroma, so colorful areas near the
+    // center win over large gray ones.
+    let mut colorful: Vec<(f32, f32, f32)> = clusters.iter()
+        .filter_map(|c| {
+            let chroma = (c.lab[1] * c.lab[1] + c.lab[2] * c.lab[2]).sqrt();
+            (chroma >= MIN_C).then(|| (c.weight * chroma.powf(C_WEIGHT_GAMMA), c.lab[2].atan2(c.lab[1]), chroma))
+        })
+        .collect();
+    colorful.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let total: f32 = colorful.iter().map(|c| c.0).sum();
+
+    let mut out: Vec<(f32, f32)> = Vec::new();
+    for (score, hue, chroma) in colorful {
+        if out.len() == MAX_ACCENTS { break; }
+        // The strongest cluster always counts, even when it is a small one.
+        if !out.is_empty() && score < total * MIN_ACCENT_SHARE { break; }
+        let distinct = out.iter().all(|(h, _)| hue_delta(*h, hue).abs().to_degrees() >= MIN_ACCENT_SEPARATION_DEG);
+        if distinct { out.push((hue, chroma.clamp(0.0, 0.30))); }
+    }
+    out
+}
+
+// Thumbnail pixels in OkLab, weighted towards the center like the histogram.
+fn samples(img: &DynamicImage) -> Vec<Sample> {
+    let rgb = img.thumbnail(MAX_THUMB, MAX_THUMB).to_rgb8();
+    let (w, h) = rgb.dimensions();
+    let (cx, cy) = (w as f32 / 2.0, h as f32 / 2.0);
+    let sigma = CENTER_SIGMA_FRAC * (w.max(h) as f32 / 2.0);
+    let inv2s2 = 1.0 / (2.0 * sigma * sigma).max(1e-6);
+    rgb.enumerate_pixels()
+        .map(|(x, y, p)| {
+            let (l, a, b) = srgb8_to_oklab(p[0], p[1], p[2]);
+            let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+            Sample { lab: [l, a, b], weight: (-(dx * dx + dy * dy) * inv2s2).exp() }
+        })
+        .collect()
+}
+
+fn distance2(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+// Weighted mean of `samples`, or None when they weigh nothing.
+fn mean(samples: &[Sample]) -> Option<Cluster> {
+    let weight: f32 = samples.iter().map(|s| s.weight).sum();
+    if weight <= 0.0 { return None; }
+    let mut lab = [0.0; 3];
+    for s in samples {
+        for (axis, v) in lab.iter_mut().enumerate() { *v += s.lab[axis] * s.weight; }
+    }
+    Some(Cluster { lab: lab.map(|v| v / weight), weight })
+}
+
+// Weighted k-means with k-means++ seeding from a fixed-seed SmallRng.
+fn kmeans(samples: &[Sample]) -> Vec<Cluster> {
+    if samples.is_empty() { return Vec::new(); }
+    let mut rng = SmallRng::seed_from_u64(SEED);
+    let mut centers: Vec<[f32; 3]> = vec![samples[rng.gen_range(0..samples.len())].lab];
+    while centers.len() < K {
+        // Next center drawn with probability proportional to weight × squared distance.
+        let scores: Vec<f32> = samples.iter()
+            .map(|s| s.weight * centers.iter().map(|c| distance2(&s.lab, c)).fold(f32::INFINITY, f32::min))
+            .collect();
+        let total: f32 = scores.iter().sum();
+        if total <= 0.0 { break; }
+        let mut pick = rng.gen_range(0.0..total);
+        let next = scores.iter().position(|s| { pick -= s; pick <= 0.0 }).unwrap_or(samples.len() - 1);
+        centers.push(samples[next].lab);
+    }
+
+    let mut assignment = vec![0usize; samples.len()];
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut moved = false;
+        for (s, slot) in samples.iter().zip(assignment.iter_mut()) {
+            let nearest = (0..centers.len())
+                .min_by(|a, b| distance2(&s.lab, &centers[*a]).total_cmp(&distance2(&s.lab, &centers[*b])))
+                .unwrap_or(0);
+            if nearest != *slot { *slot = nearest; moved = true; }
+        }
+        for (i, center) in centers.iter_mut().enumerate() {
+            let members: Vec<Sample> = samples.iter().zip(&assignment).filter(|(_, a)| **a == i).map(|(s, _)| *s).collect();
+            if let Some(c) = mean(&members) { *center = c.lab; }
+        }
+        if !moved { break; }
+    }
+
+    (0..centers.len())
+        .filter_map(|i| {
+            let members: Vec<Sample> = samples.iter().zip(&assignment).filter(|(_, a)| **a == i).map(|(s, _)| *s).collect();
+            mean(&members)
+        })
+        .collect()
+}
+
+// Median cut: keep splitting the box with the widest spread (times its weight) at the weighted
+// median of its longest axis until there are `K` boxes.
+fn median_cut(samples: &[Sample]) -> Vec<Cluster> {
+    let mut boxes: Vec<Vec<Sample>> = vec![samples.to_vec()];
+    while boxes.len() < K {
+        let widest = boxes.iter().enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| {
+                let (axis, range) = longest_axis(b);
+                let weight: f32 = b.iter().map(|s| s.weight).sum();
+                (i, axis, range * weight)
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2));
+        let Some((i, axis, spread)) = widest else { break };
+        if spread <= 0.0 { break; }
+
+        let mut members = boxes.swap_remove(i);
+        members.sort_by(|a, b| a.lab[axis].total_cmp(&b.lab[axis]));
+        let half = members.iter().map(|s| s.weight).sum::<f32>() / 2.0;
+        let mut acc = 0.0;
+        let split = members.iter().position(|s| { acc += s.weight; acc >= half }).unwrap_or(0);
+        let upper = members.split_off((split + 1).clamp(1, members.len() - 1));
+        boxes.push(members);
+        boxes.push(upper);
+    }
+    boxes.iter().filter_map(|b| mean(b)).collect()
+}
+
+fn longest_axis(samples: &[Sample]) -> (usize, f32) {
+    (0..3)
+        .map(|axis| {
+            let (lo, hi) = samples.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), s| (lo.min(s.lab[axis]), hi.max(s.lab[axis])));
+            (axis, hi - lo)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, 0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    // Left half orange, right half teal, a gray stripe in between.
+    fn two_hues() -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(64, 64, |x, _| match x {
+            0..30 => Rgb([230, 120, 30]),
+            30..34 => Rgb([128, 128, 128]),
+            _ => Rgb([20, 170, 170]),
+        }))
+    }
+
+    fn labs(clusters: &[Cluster]) -> Vec<([f32; 3], f32)> {
+        clusters.iter().map(|c| (c.lab, c.weight)).collect()
+    }
+
+    #[test]
+    fn clustering_is_reproducible() {
+        let samples = samples(&two_hues());
+        assert_eq!(labs(&kmeans(&samples)), labs(&kmeans(&samples)));
+        assert_eq!(labs(&median_cut(&samples)), labs(&median_cut(&samples)));
+        for method in [Extraction::KMeans, Extraction::MedianCut] {
+            assert_eq!(accent_hues(&two_hues(), method), accent_hues(&two_hues(), method));
+        }
+    }
+
+    #[test]
+    fn separated_hues_become_two_accents() {
+        for method in [Extraction::KMeans, Extraction::MedianCut] {
+            let accents = accent_hues(&two_hues(), method);
+            assert_eq!(accents.len(), 2, "{method}: {accents:?}");
+            let apart = hue_delta(accents[0].0, accents[1].0).abs().to_degrees();
+            assert!(apart >= MIN_ACCENT_SEPARATION_DEG, "{method}: {apart}° apart");
+        }
+    }
+
+    #[test]
+    fn histogram_has_no_clusters() {
+        assert!(accent_hues(&two_hues(), Extraction::Histogram).is_empty());
+    }
+}
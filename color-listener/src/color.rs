@@ -0,0 +1,115 @@
+// Shared color math: sRGB <-> OkLab/OkLCh conversions, hex helpers and WCAG contrast.
+// Kept free of any palette policy; the processor decides *which* colors to build.
+
+use std::f32::consts::PI;
+
+pub type Rgb = (u8, u8, u8);
+
+pub fn srgb_to_linear(x: f32) -> f32 {
+    if x <= 0.04045 { x / 12.92 } else { ((x + 0.055) / 1.055).powf(2.4) }
+}
+pub fn linear_to_srgb(x: f32) -> f32 {
+    if x <= 0.0031308 { 12.92 * x } else { 1.055 * x.powf(1.0/2.4) - 0.055 }
+}
+
+#[allow(clippy::excessive_precision)]
+pub fn srgb8_to_oklab(r: u8, g: u8, b: u8) -> (f32,f32,f32) {
+    let r = srgb_to_linear(r as f32 / 255.0);
+    let g = srgb_to_linear(g as f32 / 255.0);
+    let b = srgb_to_linear(b as f32 / 255.0);
+
+    let l = 0.4122214708*r + 0.5363325363*g + 0.0514459929*b;
+    let m = 0.2119034982*r + 0.6806995451*g + 0.1073969566*b;
+    let s = 0.0883024619*r + 0.2817188376*g + 0.6299787005*b;
+
+    let l_ = l.cbrt(); let m_ = m.cbrt(); let s_ = s.cbrt();
+
+    let ok_l = 0.2104542553*l_ + 0.7936177850*m_ - 0.0040720468*s_;
+    let ok_a = 1.9779984951*l_ - 2.4285922050*m_ + 0.4505937099*s_;
+    let ok_b = 0.0259040371*l_ + 0.7827717662*m_ - 0.8086757660*s_;
+    (ok_l, ok_a, ok_b)
+}
+
+#[allow(clippy::excessive_precision)]
+pub fn oklab_to_srgb(l: f32, a: f32, b: f32) -> (f32,f32,f32) {
+    let l_ = l + 0.3963377774*a + 0.2158037573*b;
+    let m_ = l - 0.1055613458*a - 0.0638541728*b;
+    let s_ = l - 0.0894841775*a - 1.2914855480*b;
+
+    let l3 = l_*l_*l_;
+    let m3 = m_*m_*m_;
+    let s3 = s_*s_*s_;
+
+    let r =  4.0767416621*l3 - 3.3077115913*m3 + 0.2309699292*s3;
+    let g = -1.2684380046*l3 + 2.6097574011*m3 - 0.3413193965*s3;
+    let b = -0.0041960863*l3 - 0.7034186147*m3 + 1.7076147010*s3;
+
+    (linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b))
+}
+
+/// OkLCh -> `#RRGGBB`, shrinking chroma until the color fits inside sRGB.
+pub fn oklch_to_hex_gamut_ok(l: f32, mut c: f32, h: f32) -> String {
+    for _ in 0..12 {
+        let a = c * h.cos();
+        let b = c * h.sin();
+        let (r, g, bb) = oklab_to_srgb(l, a, b);
+        if (0.0..=1.0).contains(&r) && (0.0..=1.0).contains(&g) && (0.0..=1.0).contains(&bb) {
+            return to_hex((r*255.0).round() as u8,
+                          (g*255.0).round() as u8,
+                          (bb*255.0).round() as u8);
+        }
+        c *= 0.88; // shrink chroma until inside sRGB
+    }
+    let a = c * h.cos(); let b = c * h.sin();
+    let (r, g, bb) = oklab_to_srgb(l, a, b);
+    to_hex((r.clamp(0.0,1.0)*255.0).round() as u8,
+           (g.clamp(0.0,1.0)*255.0).round() as u8,
+           (bb.clamp(0.0,1.0)*255.0).round() as u8)
+}
+
+pub fn hex_to_oklch(hex: &str) -> (f32,f32,f32) {
+    let (r,g,b) = hex_to_rgb(hex).unwrap_or((127,127,127));
+    let (l,a,b2) = srgb8_to_oklab(r,g,b);
+    let c = (a*a + b2*b2).sqrt();
+    let h = b2.atan2(a);
+    (l,c,h)
+}
+
+pub fn to_hex(r:u8,g:u8,b:u8)->String { format!("#{:02X}{:02X}{:02X}", r,g,b) }
+
+/// Parse `#RRGGBB` (case-insensitive). Anything else is `None`.
+pub fn hex_to_rgb(hex: &str) -> Option<Rgb> {
+    let h = hex.strip_prefix('#')?;
+    if h.len() != 6 || !h.is_ascii() { return None; }
+    Some((
+        u8::from_str_radix(&h[0..2],16).ok()?,
+        u8::from_str_radix(&h[2..4],16).ok()?,
+        u8::from_str_radix(&h[4..6],16).ok()?
+    ))
+}
+
+pub fn relative_luminance(rgb: Rgb) -> f32 {
+    let (r,g,b) = rgb;
+    let lin = |c: u8| srgb_to_linear(c as f32 / 255.0);
+    0.2126*lin(r) + 0.7152*lin(g) + 0.0722*lin(b)
+}
+pub fn contrast_ratio(a: Rgb, b: Rgb) -> f32 {
+    let la = relative_luminance(a);
+    let lb = relative_luminance(b);
+    let (hi, lo) = if la > lb { (la, lb) } else { (lb, la) };
+    (hi + 0.05) / (lo + 0.05)
+}
+/// WCAG contrast between two `#RRGGBB` strings; unparsable input counts as mid gray.
+pub fn contrast_ratio_hex(a: &str, b: &str) -> f32 {
+    let ar = hex_to_rgb(a).unwrap_or((127,127,127));
+    let br = hex_to_rgb(b).unwrap_or((127,127,127));
+    contrast_ratio(ar, br)
+}
+
+/// Signed shortest distance from hue `from` to hue `to` in radians, in (-PI, PI].
+pub fn hue_delta(from: f32, to: f32) -> f32 {
+    let mut d = (to - from) % (2.0 * PI);
+    if d > PI { d -= 2.0 * PI; }
+    if d <= -PI { d += 2.0 * PI; }
+    d
+}
@@ -0,0 +1,137 @@
+// `~/.config/color-listener/config.toml`: defaults for everything the CLI can set.
+//
+// watcher   = "hyprpaper"
+// profile   = "dark"          # dark | light | high-contrast | vivid | muted
+// watchfile = "~/.config/hypr/hyprpaper.conf"
+// monitor   = "focused"       # hyprpaper: focused | <monitor name> | blend (also --monitor)
+// debounce  = 300             # ms a wallpaper change has to settle before it is themed (also --debounce)
+// colorfile = "~/.config/dotfiles/colors.scss"
+// cache     = true            # reuse palettes of wallpapers seen before (see `color-listener cache`)
+// history   = 20              # applied palettes kept for `ctl undo`
+// validation = "warn"         # palettes failing contrast checks: warn | refuse | correct (also --validation)
+// socket    = "/run/user/1000/color-listener.sock"   # control socket, default $XDG_RUNTIME_DIR (see `color-listener ctl`)
+//
+// [schedule]                # switch profiles by time of day, re-theming the current wallpaper
+// latitude  = 52.52          # light from sunrise to sunset here, dark at night
+// longitude = 13.40
+// day       = "light"
+// night     = "dark"
+//
+// [schedule.times]           # or fixed clock times instead of the sun
+// "07:00" = "light"
+// "19:30" = "dark"
+//
+// [extraction]               # how each profile finds its accents (also --extraction, for all profiles)
+// vivid = "kmeans"           # histogram (default) | kmeans | median-cut; the last two add accent-2, accent-3, ...
+//
+// [appliers.kitty]
+// enabled = true
+// required = false          # a kitty failure no longer rolls the other targets back
+// conf = "~/.config/dotfiles/kitty/kitty.conf"
+//
+// [appliers.gtk]
+// enabled = true            # appliers listed as opt-in only run when enabled
+//
+// Every key inside `[appliers.<name>]` other than `enabled` and `required` must be in that applier's schema
+// (`color-listener --list-appliers`). CLI flags override whatever the file says.
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::applier::ApplierSettings;
+use crate::paths::expand_home;
+
+pub const DEFAULT_CONFIG_PATH: &str = "~/.config/color-listener/config.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    pub watcher: Option<String>,
+    pub watchfile: Option<String>,
+    pub monitor: Option<String>,
+    pub debounce: Option<u64>,
+    pub colorfile: Option<String>,
+    pub profile: Option<String>,
+    pub cache: Option<bool>,
+    pub socket: Option<String>,
+    pub history: Option<usize>,
+    pub validation: Option<String>,
+    pub schedule: Option<ScheduleConfig>,
+    /// Profile name -> extraction method; see `Extractions` in profile.rs.
+    #[serde(default)]
+    pub extraction: BTreeMap<String, String>,
+    #[serde(default)]
+    pub appliers: BTreeMap<String, toml::Table>,
+}
+
+/// `[schedule]`: either a position with `day`/`night` profiles or a `times` table; see schedule.rs.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleConfig {
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub day: Option<String>,
+    pub night: Option<String>,
+    #[serde(default)]
+    pub times: BTreeMap<String, String>,
+}
+
+/// Per-applier view of `[appliers.*]`: which are switched off, and the stringified settings of each.
+#[derive(Debug, Default)]
+pub struct ApplierSections {
+    pub enabled: Vec<String>,
+    pub disabled: Vec<String>,
+    pub settings: HashMap<String, ApplierSettings>,
+}
+
+impl FileConfig {
+    /// Load the config file. A missing file is fine for the default location but an error
+    /// when the user pointed `--config` at it explicitly.
+    pub fn load(path: &Path, explicit: bool) -> Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && !explicit => return Ok(Self::default()),
+            Err(e) => return Err(anyhow!("read config {}: {}", path.display(), e)),
+        };
+        toml::from_str(&text).with_context(|| format!("invalid config {}", path.display()))
+    }
+
+    pub fn default_path() -> PathBuf {
+        expand_home(DEFAULT_CONFIG_PATH)
+    }
+
+    pub fn applier_sections(&self) -> Result<ApplierSections> {
+        let mut out = ApplierSections::default();
+        for (name, table) in &self.appliers {
+            let name = name.to_lowercase();
+            let mut values = ApplierSettings::new();
+            for (key, value) in table {
+                if key == "enabled" {
+                    match value.as_bool() {
+                        Some(true) => out.enabled.push(name.clone()),
+                        Some(false) => out.disabled.push(name.clone()),
+                        None => return Err(anyhow!("appliers.{}.enabled must be true or false", name)),
+                    }
+                    continue;
+                }
+                values.insert(key.clone(), scalar_to_string(value)
+                    .ok_or_else(|| anyhow!("appliers.{}.{} must be a string, number or boolean", name, key))?);
+            }
+            out.settings.insert(name, values);
+        }
+        Ok(out)
+    }
+}
+
+fn scalar_to_string(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Integer(i) => Some(i.to_string()),
+        toml::Value::Float(f) => Some(f.to_string()),
+        toml::Value::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
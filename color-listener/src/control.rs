@@ -0,0 +1,152 @@
+// Control socket: one JSON object per line in, one JSON object per line out.
+//
+//   {"cmd":"status"}
+//   {"cmd":"reapply"}
+//   {"cmd":"set-palette","primary":"#1A1B26","secondary":"#7AA2F7"}
+//   {"cmd":"lock"}   {"cmd":"unlock"}
+//   {"cmd":"profile","name":"light"}
+//   {"cmd":"history"}   {"cmd":"undo"}
+//   {"cmd":"favourite","name":"dusk"}   {"cmd":"unfavourite","name":"dusk"}
+//   {"cmd":"favourites"}   {"cmd":"apply-favourite","name":"dusk"}
+//
+// Replies are {"ok":true,...} or {"ok":false,"error":"..."}; `status` adds the daemon state,
+// `history` and `favourites` add an `entries` field.
+// `color-listener ctl <cmd> [args]` is the matching client for scripts and bar widgets.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::task;
+
+use crate::daemon::Daemon;
+use crate::profile::Profile;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "kebab-case")]
+pub enum Request {
+    Status,
+    Reapply,
+    SetPalette { primary: String, secondary: String },
+    Lock,
+    Unlock,
+    Profile { name: String },
+    History,
+    Undo,
+    Favourite { name: String },
+    Unfavourite { name: String },
+    Favourites,
+    ApplyFavourite { name: String },
+}
+
+impl Request {
+    /// From `ctl` arguments, e.g. `["set-palette", "#111111", "#EEEEEE"]`.
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let arg = |i: usize| args.get(i).cloned().ok_or_else(|| anyhow!("{} needs more arguments", args[0]));
+        let req = match args.first().map(String::as_str) {
+            Some("status") => Request::Status,
+            Some("reapply") => Request::Reapply,
+            Some("set-palette") => Request::SetPalette { primary: arg(1)?, secondary: arg(2)? },
+            Some("lock") => Request::Lock,
+            Some("unlock") => Request::Unlock,
+            Some("profile") => Request::Profile { name: arg(1)? },
+            Some("history") => Request::History,
+            Some("undo") => Request::Undo,
+            Some("favourite") => Request::Favourite { name: arg(1)? },
+            Some("unfavourite") => Request::Unfavourite { name: arg(1)? },
+            Some("favourites") => Request::Favourites,
+            Some("apply-favourite") => Request::ApplyFavourite { name: arg(1)? },
+            Some(other) => return Err(anyhow!("unknown command {}", other)),
+            None => return Err(anyhow!("usage: color-listener ctl status|reapply|set-palette <primary> <secondary>|lock|unlock|profile <name>\n       \
+                                        |history|undo|favourite <name>|unfavourite <name>|favourites|apply-favourite <name>")),
+        };
+        Ok(req)
+    }
+}
+
+pub fn default_socket_path() -> PathBuf {
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR") { return PathBuf::from(dir).join("color-listener.sock"); }
+    PathBuf::from("/tmp/color-listener.sock")
+}
+
+/// Bind `path` and answer requests in the background. A leftover socket file from a crashed
+/// run is replaced; one that still has a listener behind it is an error.
+pub async fn serve(path: &Path, daemon: Arc<Mutex<Daemon>>) -> Result<()> {
+    if path.exists() {
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(anyhow!("another color-listener is already listening on {}", path.display()));
+        }
+        std::fs::remove_file(path).with_context(|| format!("remove stale socket {}", path.display()))?;
+    }
+    let listener = UnixListener::bind(path).with_context(|| format!("bind {}", path.display()))?;
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => { tokio::spawn(serve_client(stream, daemon.clone())); }
+                Err(e) => { eprintln!("[control] accept error: {e}"); break; }
+            }
+        }
+    });
+    Ok(())
+}
+
+async fn serve_client(stream: UnixStream, daemon: Arc<Mutex<Daemon>>) {
+    let (read, mut write) = stream.into_split();
+    let mut lines = tokio::io::BufReader::new(read).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() { continue; }
+        let reply = match serde_json::from_str::<Request>(&line) {
+            Ok(req) => {
+                let daemon = daemon.clone();
+                task::spawn_blocking(move || handle(&mut daemon.lock().unwrap(), req)).await
+                    .unwrap_or_else(|e| json!({ "ok": false, "error": e.to_string() }))
+            }
+            Err(e) => json!({ "ok": false, "error": format!("bad request: {e}") }),
+        };
+        let mut out = reply.to_string();
+        out.push('\n');
+        if write.write_all(out.as_bytes()).await.is_err() { break; }
+    }
+}
+
+fn handle(daemon: &mut Daemon, req: Request) -> Value {
+    let result = match req {
+        Request::Status => {
+            let mut status = daemon.status();
+            status["ok"] = json!(true);
+            return status;
+        }
+        Request::Reapply => daemon.reapply(),
+        Request::SetPalette { primary, secondary } => daemon.set_palette(&primary, &secondary),
+        Request::Lock => { daemon.lock(); Ok(()) }
+        Request::Unlock => daemon.unlock(),
+        Request::Profile { name } => Profile::parse(&name).and_then(|p| daemon.set_profile(p)),
+        Request::History => return json!({ "ok": true, "entries": daemon.history() }),
+        Request::Undo => daemon.undo(),
+        Request::Favourite { name } => daemon.add_favourite(&name),
+        Request::Unfavourite { name } => daemon.remove_favourite(&name),
+        Request::Favourites => return json!({ "ok": true, "entries": daemon.favourites() }),
+        Request::ApplyFavourite { name } => daemon.apply_favourite(&name),
+    };
+    match result {
+        Ok(()) => json!({ "ok": true }),
+        Err(e) => json!({ "ok": false, "error": e.to_string() }),
+    }
+}
+
+/// Send one request to a running listener and return its reply.
+pub fn send(path: &Path, req: &Request) -> Result<Value> {
+    let mut stream = std::os::unix::net::UnixStream::connect(path)
+        .with_context(|| format!("connect {} (is color-listener running?)", path.display()))?;
+    let mut line = serde_json::to_string(req)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    serde_json::from_str(&reply).with_context(|| format!("bad reply from {}", path.display()))
+}
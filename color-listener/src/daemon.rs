@@ -0,0 +1,383 @@
+// State of the running listener, shared by the wallpaper loop and the control socket.
+// Every palette change (wallpaper, manual override, profile switch) goes through `apply`,
+// which is all-or-nothing across the colors file and every required applier.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::applier::Applier;
+use crate::cache::PaletteCache;
+use crate::color::{hex_to_rgb, to_hex};
+use crate::history::{History, HistoryEntry};
+use crate::palette::Palette;
+use crate::processor::{self, Processor};
+use crate::profile::{Extractions, Profile};
+use crate::transaction::Snapshot;
+use crate::validate::{self, Policy, Report};
+use crate::watchers::WallpaperEvent;
+
+/// Where the current palette came from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Source {
+    Wallpaper,
+    Manual,
+}
+
+/// Outcome of the last `apply` for one applier, as reported by `status`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApplierStatus {
+    pub ok: bool,
+    pub message: String,
+    pub warnings: Vec<String>,
+}
+
+/// Numbers wallpaper events as they arrive. Work for one stops at its next checkpoint as soon
+/// as a newer one is issued, so a burst of wallpapers only ends in the last one's theme.
+#[derive(Clone, Default)]
+pub struct Generations(Arc<AtomicU64>);
+
+impl Generations {
+    pub fn issue(&self) -> Generation {
+        let id = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+        Generation { latest: self.0.clone(), id }
+    }
+}
+
+pub struct Generation {
+    latest: Arc<AtomicU64>,
+    id: u64,
+}
+
+impl Generation {
+    /// A newer wallpaper has arrived since this one.
+    pub fn superseded(&self) -> bool {
+        self.latest.load(Ordering::SeqCst) != self.id
+    }
+}
+
+pub struct Daemon {
+    colorfile: PathBuf,
+    profile: Profile,
+    appliers: Vec<Box<dyn Applier>>,
+    /// Appliers marked `required = false`: their failures are reported but do not roll back.
+    optional: Vec<String>,
+    use_cache: bool,
+    /// Last wallpaper the watcher reported, kept even while locked so `unlock`/`profile` can use it.
+    wallpaper: Option<WallpaperEvent>,
+    /// Wallpaper the current palette was derived from, if it came from one.
+    applied_wallpaper: Option<PathBuf>,
+    palette: Option<Palette>,
+    source: Source,
+    locked: bool,
+    results: BTreeMap<String, ApplierStatus>,
+    /// Why the last theme switch was rolled back, cleared by the next successful one.
+    last_error: Option<String>,
+    history: History,
+    /// What to do with new palettes that fail validation.
+    validation: Policy,
+    /// Validation report of the current palette.
+    report: Option<Report>,
+    /// Extraction per profile, applied whenever the profile changes.
+    extractions: Extractions,
+}
+
+impl Daemon {
+    pub fn new(colorfile: PathBuf, profile: Profile, appliers: Vec<Box<dyn Applier>>, optional: Vec<String>, use_cache: bool, history: History, validation: Policy) -> Self {
+        Self {
+            colorfile,
+            profile,
+            appliers,
+            optional,
+            use_cache,
+            wallpaper: None,
+            applied_wallpaper: None,
+            palette: None,
+            source: Source::Wallpaper,
+            locked: false,
+            results: BTreeMap::new(),
+            last_error: None,
+            history,
+            validation,
+            report: None,
+            extractions: Extractions::default(),
+        }
+    }
+
+    /// Pick the extraction of every profile switched to later from `extractions`.
+    pub fn with_extractions(mut self, extractions: Extractions) -> Self {
+        self.extractions = extractions;
+        self
+    }
+
+    /// Theme from a new wallpaper, unless a newer one arrives first: superseded work is dropped
+    /// before extraction and again before applying. The daemon is only locked to read the
+    /// profile and to apply, so the control socket and the schedule are not held up by an
+    /// extraction. Once the appliers run they finish, so the desktop never sits half-switched.
+    pub fn on_wallpaper(daemon: &Mutex<Self>, ev: WallpaperEvent, generation: &Generation) {
+        if generation.superseded() {
+            println!("[daemon] skipping {}: a newer wallpaper arrived", ev.path.display());
+            return;
+        }
+        println!("[daemon] new wallpaper: {} ({}x{})", ev.path.display(), ev.image.width(), ev.image.height());
+        let (profile, use_cache) = {
+            let mut this = daemon.lock().unwrap();
+            if this.locked {
+                println!("[daemon] locked, keeping the current theme");
+                this.wallpaper = Some(ev);
+                return;
+            }
+            (this.profile.clone(), this.use_cache)
+        };
+
+        let palette = extract(&ev, profile.clone(), use_cache);
+        if generation.superseded() {
+            println!("[daemon] dropping the palette of {}: a newer wallpaper arrived", ev.path.display());
+            return;
+        }
+
+        let mut this = daemon.lock().unwrap();
+        // Checked again under the lock: a newer wallpaper owns the theme from here on.
+        if generation.superseded() {
+            println!("[daemon] dropping the palette of {}: a newer wallpaper arrived", ev.path.display());
+            return;
+        }
+        let path = ev.path.clone();
+        this.wallpaper = Some(ev);
+        // Locked or switched profile while extracting: the palette no longer fits.
+        let result = if this.locked {
+            println!("[daemon] locked, keeping the current theme");
+            Ok(())
+        } else if this.profile != profile {
+            this.theme_from_wallpaper()
+        } else {
+            this.adopt(path, palette)
+        };
+        if let Err(e) = result {
+            eprintln!("[daemon] {e}");
+        }
+    }
+
+    /// Write the current palette and run every applier again, e.g. after a target app restarted.
+    pub fn reapply(&mut self) -> Result<()> {
+        let palette = self.palette.clone().ok_or_else(|| anyhow!("no palette applied yet"))?;
+        self.apply(palette)
+    }
+
+    /// Manual override from two `#RRGGBB` colors; sticks until the next wallpaper change.
+    pub fn set_palette(&mut self, primary: &str, secondary: &str) -> Result<()> {
+        let primary = normalize_hex(primary)?;
+        let secondary = normalize_hex(secondary)?;
+        let palette = processor::palette_from_colors(&primary, &secondary, &self.profile);
+        let palette = self.vet(palette)?;
+        self.apply(palette)?;
+        self.source = Source::Manual;
+        self.remember();
+        Ok(())
+    }
+
+    /// Go back to the theme before the current one (including its profile).
+    pub fn undo(&mut self) -> Result<()> {
+        let entry = self.history.previous()?;
+        self.apply(entry.palette.clone())?;
+        if let Some(profile) = Profile::named(&entry.profile) { self.profile = self.extractions.apply(profile); }
+        self.source = entry.source;
+        if entry.source == Source::Wallpaper { self.applied_wallpaper = entry.wallpaper; }
+        if let Err(e) = self.history.drop_current() { eprintln!("[history] {e}"); }
+        Ok(())
+    }
+
+    pub fn history(&self) -> Value {
+        json!(self.history.entries().collect::<Vec<_>>())
+    }
+
+    /// Pin the current theme under `name`, replacing an existing favourite of that name.
+    pub fn add_favourite(&mut self, name: &str) -> Result<()> {
+        let entry = self.current_entry().ok_or_else(|| anyhow!("no palette applied yet"))?;
+        self.history.add_favourite(name, entry)
+    }
+
+    pub fn remove_favourite(&mut self, name: &str) -> Result<()> {
+        self.history.remove_favourite(name)
+    }
+
+    pub fn favourites(&self) -> Value {
+        json!(self.history.favourites())
+    }
+
+    /// Apply a pinned palette regardless of the wallpaper; like `set-palette`, it sticks until
+    /// the next wallpaper change.
+    pub fn apply_favourite(&mut self, name: &str) -> Result<()> {
+        let entry = self.history.favourite(name)?;
+        self.apply(entry.palette)?;
+        self.source = Source::Manual;
+        self.remember();
+        Ok(())
+    }
+
+    pub fn lock(&mut self) {
+        self.locked = true;
+    }
+
+    /// Unfreeze; catches up with a wallpaper that changed while locked.
+    pub fn unlock(&mut self) -> Result<()> {
+        self.locked = false;
+        if self.pending_wallpaper().is_some() { self.theme_from_wallpaper()?; }
+        Ok(())
+    }
+
+    /// Switch profile and re-derive the theme from the current wallpaper unless locked or overridden.
+    pub fn set_profile(&mut self, profile: Profile) -> Result<()> {
+        self.profile = self.extractions.apply(profile);
+        if self.locked || self.source == Source::Manual || self.wallpaper.is_none() { return Ok(()); }
+        self.theme_from_wallpaper()
+    }
+
+    pub fn status(&self) -> Value {
+        json!({
+            "wallpaper": self.applied_wallpaper.as_ref().map(|p| p.display().to_string()),
+            "pending_wallpaper": self.pending_wallpaper().map(|p| p.display().to_string()),
+            "profile": self.profile.name,
+            "extraction": self.profile.extraction.name(),
+            "source": self.source,
+            "locked": self.locked,
+            "palette": self.palette,
+            "validation": self.report,
+            "appliers": self.results,
+            "last_error": self.last_error,
+        })
+    }
+
+    /// Last wallpaper the watcher reported when the theme was not derived from it (locked,
+    /// overridden, or refused by validation).
+    fn pending_wallpaper(&self) -> Option<&PathBuf> {
+        self.wallpaper.as_ref().map(|ev| &ev.path).filter(|path| self.applied_wallpaper.as_ref() != Some(*path))
+    }
+
+    fn theme_from_wallpaper(&mut self) -> Result<()> {
+        let ev = self.wallpaper.as_ref().ok_or_else(|| anyhow!("no wallpaper seen yet"))?;
+        let palette = extract(ev, self.profile.clone(), self.use_cache);
+        let path = ev.path.clone();
+        self.adopt(path, palette)
+    }
+
+    /// Vet and apply a palette derived from the wallpaper at `path`.
+    fn adopt(&mut self, path: PathBuf, palette: Palette) -> Result<()> {
+        let palette = self.vet(palette)?;
+        self.apply(palette)?;
+        self.source = Source::Wallpaper;
+        self.applied_wallpaper = Some(path);
+        self.remember();
+        Ok(())
+    }
+
+    /// The `validation` policy for a newly derived palette. Palettes coming back from history
+    /// or favourites were vetted when first applied and skip this.
+    fn vet(&mut self, palette: Palette) -> Result<Palette> {
+        let report = validate::validate(&palette, &self.profile);
+        if report.passed { return Ok(palette); }
+        match self.validation {
+            Policy::Warn => Ok(palette),
+            Policy::Refuse => {
+                let failures: Vec<String> = report.failures().map(|c| format!("{} {:.2} < {:.1}", c.name, c.ratio, c.min)).collect();
+                let err = format!("palette fails validation, keeping the current theme: {}", failures.join(", "));
+                self.last_error = Some(err.clone());
+                Err(anyhow!(err))
+            }
+            Policy::Correct => {
+                let corrected = validate::correct(&palette, &self.profile);
+                let changed = palette.named_colors().iter().zip(corrected.named_colors())
+                    .filter(|(before, after)| before.1 != after.1)
+                    .map(|(before, after)| format!("{} {} -> {}", before.0, before.1, after.1))
+                    .collect::<Vec<_>>();
+                println!("[validate] corrected {}", changed.join(", "));
+                Ok(corrected)
+            }
+        }
+    }
+
+    fn current_entry(&self) -> Option<HistoryEntry> {
+        let palette = self.palette.clone()?;
+        // The wallpaper this palette came from, not whatever the watcher saw last.
+        let wallpaper = if self.source == Source::Wallpaper { self.applied_wallpaper.clone() } else { None };
+        Some(HistoryEntry::now(wallpaper, self.profile.name, self.source, palette))
+    }
+
+    /// Record the theme that was just applied; history problems never undo a switch.
+    fn remember(&mut self) {
+        let Some(entry) = self.current_entry() else { return };
+        if let Err(e) = self.history.push(entry) { eprintln!("[history] {e}"); }
+    }
+
+    /// Snapshot every target, write the colors file and run every applier. If the colors file
+    /// or a required applier fails, all targets are restored and the appliers reloaded, so the
+    /// desktop stays on the previous theme instead of ending up half-switched.
+    fn apply(&mut self, palette: Palette) -> Result<()> {
+        let targets = std::iter::once(self.colorfile.clone()).chain(self.appliers.iter().flat_map(|ap| ap.targets()));
+        let snapshot = Snapshot::capture(targets)?;
+
+        let mut failed: Vec<String> = Vec::new();
+        if let Err(e) = processor::write_colors_file(&self.colorfile, &palette) {
+            failed.push(format!("colorfile: {e}"));
+        } else {
+            println!("[processor] wrote colors primary={} secondary={}", palette.primary(), palette.secondary());
+            for ap in &self.appliers {
+                let status = match ap.apply(&palette) {
+                    Ok(report) => {
+                        println!("[applier:{}] {}", ap.name(), report.summary);
+                        for w in &report.warnings { eprintln!("[applier:{}] warn: {}", ap.name(), w); }
+                        ApplierStatus { ok: true, message: report.summary, warnings: report.warnings }
+                    }
+                    Err(e) => {
+                        let required = !self.optional.iter().any(|n| n == ap.name());
+                        eprintln!("[applier:{}] error{}: {e}", ap.name(), if required { "" } else { " (optional)" });
+                        if required { failed.push(format!("{}: {e}", ap.name())); }
+                        ApplierStatus { ok: false, message: e.to_string(), warnings: Vec::new() }
+                    }
+                };
+                self.results.insert(ap.name().to_string(), status);
+            }
+        }
+
+        if failed.is_empty() {
+            let report = validate::validate(&palette, &self.profile);
+            if report.passed { println!("[validate] {}", report.summary()); } else { eprintln!("[validate] {}", report.summary()); }
+            self.report = Some(report);
+            self.palette = Some(palette);
+            self.last_error = None;
+            return Ok(());
+        }
+        self.rollback(&snapshot);
+        let err = format!("rolled back to the previous theme: {}", failed.join("; "));
+        self.last_error = Some(err.clone());
+        Err(anyhow!(err))
+    }
+
+    fn rollback(&self, snapshot: &Snapshot) {
+        eprintln!("[daemon] rolling back all targets");
+        if let Err(e) = snapshot.restore() {
+            eprintln!("[daemon] rollback incomplete: {e}");
+        }
+        for ap in &self.appliers {
+            if let Err(e) = ap.reload() { eprintln!("[applier:{}] reload after rollback failed: {e}", ap.name()); }
+        }
+    }
+}
+
+// Palette for `ev` under `profile`, from the cache when enabled. Needs no daemon state, so the
+// wallpaper loop runs it without holding the lock.
+fn extract(ev: &WallpaperEvent, profile: Profile, use_cache: bool) -> Palette {
+    let mut proc = Processor::new(&ev.path, profile);
+    if use_cache { proc = proc.with_cache(PaletteCache::new(PaletteCache::default_dir())); }
+    proc.palette(&ev.image)
+}
+
+fn normalize_hex(hex: &str) -> Result<String> {
+    let (r, g, b) = hex_to_rgb(hex).ok_or_else(|| anyhow!("expected #RRGGBB, got {}", hex))?;
+    Ok(to_hex(r, g, b))
+}
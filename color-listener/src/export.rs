@@ -0,0 +1,55 @@
+// Text renderings of a palette for `color-listener extract`.
+
+use anyhow::{anyhow, Result};
+
+use crate::palette::Palette;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Format {
+    Json,
+    Scss,
+    Css,
+    /// One `#RRGGBB` per line in `Palette::named_colors` order.
+    Hex,
+}
+
+impl Format {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "scss" => Ok(Format::Scss),
+            "css" => Ok(Format::Css),
+            "hex" => Ok(Format::Hex),
+            _ => Err(anyhow!("unknown format {} (known: json, scss, css, hex)", s)),
+        }
+    }
+}
+
+pub fn render(palette: &Palette, format: Format) -> Result<String> {
+    let mut out = String::new();
+    match format {
+        Format::Json => {
+            out = serde_json::to_string_pretty(palette)?;
+            out.push('\n');
+        }
+        Format::Scss => {
+            out.push_str(&format!("$primary-color: {};\n$secondary-color: {};\n", palette.primary(), palette.secondary()));
+            out.push_str(&palette.scss_variables(str::to_string));
+        }
+        Format::Css => {
+            out.push_str(":root {\n");
+            out.push_str(&format!("  --primary-color: {};\n  --secondary-color: {};\n", palette.primary(), palette.secondary()));
+            for (name, hex) in palette.named_colors() {
+                out.push_str(&format!("  --{}: {};\n", name, hex));
+            }
+            out.push_str("}\n");
+        }
+        Format::Hex => {
+            for (_, hex) in palette.named_colors() {
+                out.push_str(hex);
+                out.push('\n');
+            }
+        }
+    }
+    Ok(out)
+}
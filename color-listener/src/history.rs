@@ -0,0 +1,118 @@
+// Applied palettes, newest last, plus named favourites; persisted as one JSON file in
+// `$XDG_STATE_HOME/color-listener/history.json` so undo survives restarts.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::daemon::Source;
+use crate::palette::Palette;
+
+pub const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub wallpaper: Option<PathBuf>,
+    pub profile: String,
+    pub source: Source,
+    pub palette: Palette,
+}
+
+impl HistoryEntry {
+    pub fn now(wallpaper: Option<PathBuf>, profile: &str, source: Source, palette: Palette) -> Self {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Self { timestamp, wallpaper, profile: profile.to_string(), source, palette }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Stored {
+    entries: Vec<HistoryEntry>,
+    favourites: BTreeMap<String, HistoryEntry>,
+}
+
+pub struct History {
+    path: PathBuf,
+    limit: usize,
+    stored: Stored,
+}
+
+impl History {
+    pub fn default_path() -> PathBuf {
+        if let Some(xdg) = std::env::var_os("XDG_STATE_HOME") { return PathBuf::from(xdg).join("color-listener/history.json"); }
+        let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+        home.join(".local/state/color-listener/history.json")
+    }
+
+    /// Load `path`; a missing file starts empty, an unreadable one is reported and replaced.
+    pub fn load(path: &Path, limit: usize) -> Self {
+        let stored = match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                eprintln!("[history] ignoring unreadable {}: {e}", path.display());
+                Stored::default()
+            }),
+            Err(_) => Stored::default(),
+        };
+        Self { path: path.to_path_buf(), limit: limit.max(1), stored }
+    }
+
+    /// Newest first.
+    pub fn entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.stored.entries.iter().rev()
+    }
+
+    pub fn push(&mut self, entry: HistoryEntry) -> Result<()> {
+        self.stored.entries.push(entry);
+        let excess = self.stored.entries.len().saturating_sub(self.limit);
+        self.stored.entries.drain(..excess);
+        self.save()
+    }
+
+    /// The entry before the current one, without changing anything yet.
+    pub fn previous(&self) -> Result<HistoryEntry> {
+        let n = self.stored.entries.len();
+        if n < 2 { return Err(anyhow!("nothing to undo")); }
+        Ok(self.stored.entries[n - 2].clone())
+    }
+
+    /// Forget the current entry once the previous one has been applied.
+    pub fn drop_current(&mut self) -> Result<()> {
+        self.stored.entries.pop();
+        self.save()
+    }
+
+    pub fn favourites(&self) -> &BTreeMap<String, HistoryEntry> {
+        &self.stored.favourites
+    }
+
+    pub fn favourite(&self, name: &str) -> Result<HistoryEntry> {
+        self.stored.favourites.get(name).cloned().ok_or_else(|| {
+            let known: Vec<&str> = self.stored.favourites.keys().map(String::as_str).collect();
+            anyhow!("no favourite named {} (known: {})", name, known.join(", "))
+        })
+    }
+
+    pub fn add_favourite(&mut self, name: &str, entry: HistoryEntry) -> Result<()> {
+        self.stored.favourites.insert(name.to_string(), entry);
+        self.save()
+    }
+
+    pub fn remove_favourite(&mut self, name: &str) -> Result<()> {
+        self.stored.favourites.remove(name).ok_or_else(|| anyhow!("no favourite named {}", name))?;
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&self.stored)?)?;
+        fs::rename(&tmp, &self.path).with_context(|| format!("write {}", self.path.display()))
+    }
+}
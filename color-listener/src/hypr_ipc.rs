@@ -0,0 +1,70 @@
+// Hyprland IPC: one-shot requests on `.socket.sock` (what `hyprctl` does) and the event stream
+// on `.socket2.sock`, both under the running instance's runtime dir.
+
+use anyhow::{anyhow, Context, Result};
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A stuck compositor should not hold up the apply loop for long.
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `$XDG_RUNTIME_DIR/hypr/$HYPRLAND_INSTANCE_SIGNATURE`, or `/tmp/hypr/...` for Hyprland
+/// releases before 0.40. Errors when no Hyprland instance is visible from this environment.
+pub fn instance_dir() -> Result<PathBuf> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
+        .map_err(|_| anyhow!("HYPRLAND_INSTANCE_SIGNATURE not set (is Hyprland running?)"))?;
+    let mut candidates = Vec::new();
+    if let Ok(runtime) = std::env::var("XDG_RUNTIME_DIR") {
+        candidates.push(PathBuf::from(runtime).join("hypr").join(&signature));
+    }
+    candidates.push(PathBuf::from("/tmp/hypr").join(&signature));
+    candidates.iter()
+        .find(|dir| dir.join(".socket.sock").exists())
+        .cloned()
+        .ok_or_else(|| anyhow!("no Hyprland socket in {}", candidates[0].display()))
+}
+
+/// Whether this process was started inside a Hyprland session.
+pub fn running() -> bool {
+    std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some()
+}
+
+/// Send one request (e.g. `keyword general:border_size 2`) and return Hyprland's reply.
+pub fn request(command: &str) -> Result<String> {
+    let socket = instance_dir()?.join(".socket.sock");
+    let mut stream = UnixStream::connect(&socket).with_context(|| format!("connect {}", socket.display()))?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    stream.write_all(command.as_bytes())?;
+    // Hyprland answers and closes the connection.
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).with_context(|| format!("no reply from {}", socket.display()))?;
+    Ok(reply)
+}
+
+/// Several `keyword` commands in one `[[BATCH]]` request. Every command answers `ok`; anything
+/// else is returned as the error.
+pub fn keywords(pairs: &[(&str, String)]) -> Result<()> {
+    if pairs.is_empty() { return Ok(()); }
+    let batch: Vec<String> = pairs.iter().map(|(k, v)| format!("keyword {} {}", k, v)).collect();
+    let reply = request(&format!("[[BATCH]]{}", batch.join(";")))?;
+    let errors: Vec<&str> = reply.split("\n\n").map(str::trim).filter(|r| !r.is_empty() && *r != "ok").collect();
+    if errors.is_empty() { Ok(()) } else { Err(anyhow!("hyprland: {}", errors.join("; "))) }
+}
+
+/// `.socket2.sock`, where Hyprland broadcasts `event>>data` lines (focusedmon, workspace, ...).
+pub fn event_socket() -> Result<PathBuf> {
+    Ok(instance_dir()?.join(".socket2.sock"))
+}
+
+/// Name of the monitor that currently has focus.
+pub fn focused_monitor() -> Result<Option<String>> {
+    let monitors: serde_json::Value = serde_json::from_str(&request("j/monitors")?)
+        .context("parse hyprctl monitors reply")?;
+    Ok(monitors.as_array()
+        .and_then(|all| all.iter().find(|m| m.get("focused").and_then(|f| f.as_bool()) == Some(true)))
+        .and_then(|m| m.get("name").and_then(|n| n.as_str()))
+        .map(str::to_string))
+}
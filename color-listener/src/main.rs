@@ -1,184 +1,431 @@
+use std::collections::HashMap;
 use std::env;
-use anyhow::Result;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
 mod watchers;
-use watchers::HyprpaperWatcher;
+use watchers::{DirectoryWatcher, HyprpaperWatcher, MonitorChoice, SwwwWatcher, SymlinkWatcher, WpaperdWatcher};
 mod processor; // assuming processor.rs is in src root
-use processor::Processor;
+mod color;
+mod palette;
 mod applier;
+use applier::{ApplierSettings, Registry};
+mod paths;
+use paths::expand_home;
+mod config;
+use config::FileConfig;
+mod profile;
+use profile::{Extraction, Extractions, Profile, PROFILE_NAMES};
+mod export;
+use export::Format;
+mod preview;
+mod cache;
+use cache::PaletteCache;
+mod daemon;
+use daemon::{Daemon, Generations};
+mod control;
+mod transaction;
+mod history;
+mod hypr_ipc;
+mod schedule;
+mod validate;
+mod cluster;
+use validate::Policy;
+use schedule::Schedule;
+use history::History;
 // std fs/io helpers no longer needed; Processor handles writing
 
 #[derive(Debug, Clone, Copy)]
 enum Watcher {
     Hyprpaper,
+    Symlink,
+    Swww,
+    Wpaperd,
+    Directory,
 }
 
 impl Watcher {
     fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "hyprpaper" => Some(Watcher::Hyprpaper),
+            "symlink" => Some(Watcher::Symlink),
+            "swww" => Some(Watcher::Swww),
+            "wpaperd" => Some(Watcher::Wpaperd),
+            "directory" | "dir" => Some(Watcher::Directory),
             _ => None,
         }
     }
+
+    fn name(self) -> &'static str {
+        match self {
+            Watcher::Hyprpaper => "hyprpaper",
+            Watcher::Symlink => "symlink",
+            Watcher::Swww => "swww",
+            Watcher::Wpaperd => "wpaperd",
+            Watcher::Directory => "directory",
+        }
+    }
+
+    /// What `--watchfile` points at when not given: a conf file, link, cache/state dir or image dir.
+    fn default_watchfile(self) -> String {
+        match self {
+            Watcher::Hyprpaper => "~/.config/hypr/hyprpaper.conf".to_string(),
+            Watcher::Symlink => "~/.current-wallpaper".to_string(),
+            Watcher::Swww => SwwwWatcher::default_cache_dir().to_string_lossy().into_owned(),
+            Watcher::Wpaperd => WpaperdWatcher::default_state_dir().to_string_lossy().into_owned(),
+            Watcher::Directory => "~/.config/hypr/wallpapers".to_string(),
+        }
+    }
+
+    async fn start(self, watchfile: &str, monitor: MonitorChoice, debounce: Duration) -> Result<tokio::sync::mpsc::Receiver<watchers::WallpaperEvent>> {
+        match self {
+            Watcher::Hyprpaper => HyprpaperWatcher::new(watchfile).with_monitor(monitor).start(debounce).await,
+            Watcher::Symlink => SymlinkWatcher::new(watchfile).start(debounce).await,
+            Watcher::Swww => SwwwWatcher::new(watchfile).start(debounce).await,
+            Watcher::Wpaperd => WpaperdWatcher::new(watchfile).start(debounce).await,
+            Watcher::Directory => DirectoryWatcher::new(watchfile).start(debounce).await,
+        }
+    }
 }
-#[derive(Debug, Clone, Copy)]
-enum Applier {
-    Ags,
-    Hyprland,
-    Kitty,
-    Ps1,
+fn expand_tilde(path: &str) -> String {
+    expand_home(path).to_string_lossy().into_owned()
 }
 
-impl Applier {
-    fn from_str(s: &str) -> Option<Self> {
-        match s.to_lowercase().as_str() {
-            "ags" => Some(Applier::Ags),
-            "hyprland" => Some(Applier::Hyprland),
-            "kitty" => Some(Applier::Kitty),
-            "ps1" => Some(Applier::Ps1),
-            _ => None,
+/// Honour `--config <path>` anywhere in `args`, else the default location.
+fn load_config(args: &[String]) -> Result<(PathBuf, FileConfig)> {
+    let (config_path, explicit_config) = match args.iter().position(|a| a == "--config") {
+        Some(i) => (expand_home(args.get(i + 1).ok_or_else(|| anyhow!("--config needs a value"))?), true),
+        None => (FileConfig::default_path(), false),
+    };
+    let file_config = FileConfig::load(&config_path, explicit_config)?;
+    Ok((config_path, file_config))
+}
+
+/// `color-listener extract <image> [--format json|scss|css|hex] [--profile name] [--extraction method] [--report] [--config path]`
+fn run_extract(args: &[String]) -> Result<()> {
+    let (config_path, file_config) = load_config(args)?;
+    let mut profile = match &file_config.profile {
+        Some(name) => Profile::parse(name).with_context(|| format!("in {}", config_path.display()))?,
+        None => Profile::default(),
+    };
+    let mut extractions = Extractions::from_config(&file_config.extraction)
+        .with_context(|| format!("in {}", config_path.display()))?;
+    let mut format: Option<Format> = None;
+    let mut report = false;
+    let mut image: Option<PathBuf> = None;
+
+    let mut args = args.iter().cloned();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => { flag_value(&mut args, &arg)?; }
+            "--format" => { format = Some(Format::from_str(&flag_value(&mut args, &arg)?)?); }
+            "--profile" => { profile = Profile::parse(&flag_value(&mut args, &arg)?)?; }
+            "--extraction" => { extractions.force(Extraction::parse(&flag_value(&mut args, &arg)?)?); }
+            "--report" => { report = true; }
+            _ if arg.starts_with("--") => return Err(anyhow!("unknown argument: {}", arg)),
+            _ if image.is_none() => image = Some(expand_home(&arg)),
+            _ => return Err(anyhow!("extract takes a single image, got extra argument {}", arg)),
         }
     }
+    let image = image.ok_or_else(|| anyhow!("usage: color-listener extract <image> [--format json|scss|css|hex] [--profile name] [--extraction method] [--report]"))?;
+    let profile = extractions.apply(profile);
+
+    let img = processor::decode_image(&image)?;
+    let palette = processor::extract_palette_from_wallpaper(&img, &profile);
+    if !report {
+        print!("{}", export::render(&palette, format.unwrap_or(Format::Json))?);
+        return Ok(());
+    }
+    // The validation report instead of the palette, as a table unless JSON was asked for; a
+    // failing palette makes the exit status non-zero.
+    let report = validate::validate(&palette, &profile);
+    match format {
+        Some(Format::Json) => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print!("{}", report.render()),
+    }
+    let failed = report.failures().count();
+    if failed > 0 { return Err(anyhow!("palette fails {} contrast check(s)", failed)); }
+    Ok(())
 }
 
-fn expand_tilde(path: &str) -> String {
-    if let Some(rest) = path.strip_prefix("~/") {
-        if let Some(home) = env::var_os("HOME") { return format!("{}/{}", home.to_string_lossy(), rest); }
+/// `color-listener preview <image> [--out file.png] [--profile name | --profiles a,b|all] [--extraction method] [--config path]`
+fn run_preview(args: &[String]) -> Result<()> {
+    let (config_path, file_config) = load_config(args)?;
+    let mut profiles = match &file_config.profile {
+        Some(name) => vec![Profile::parse(name).with_context(|| format!("in {}", config_path.display()))?],
+        None => vec![Profile::default()],
+    };
+    let mut extractions = Extractions::from_config(&file_config.extraction)
+        .with_context(|| format!("in {}", config_path.display()))?;
+    let mut out = PathBuf::from("palette-preview.png");
+    let mut image: Option<PathBuf> = None;
+
+    let mut args = args.iter().cloned();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => { flag_value(&mut args, &arg)?; }
+            "--out" => { out = expand_home(&flag_value(&mut args, &arg)?); }
+            "--profile" => { profiles = vec![Profile::parse(&flag_value(&mut args, &arg)?)?]; }
+            "--profiles" => {
+                let val = flag_value(&mut args, &arg)?;
+                profiles = if val == "all" {
+                    PROFILE_NAMES.iter().map(|n| Profile::parse(n)).collect::<Result<_>>()?
+                } else {
+                    val.split(',').map(|n| Profile::parse(n.trim())).collect::<Result<_>>()?
+                };
+            }
+            "--extraction" => { extractions.force(Extraction::parse(&flag_value(&mut args, &arg)?)?); }
+            _ if arg.starts_with("--") => return Err(anyhow!("unknown argument: {}", arg)),
+            _ if image.is_none() => image = Some(expand_home(&arg)),
+            _ => return Err(anyhow!("preview takes a single image, got extra argument {}", arg)),
+        }
     }
-    path.to_string()
+    let image = image.ok_or_else(|| anyhow!("usage: color-listener preview <image> [--out file.png] [--profile name | --profiles a,b|all] [--extraction method]"))?;
+
+    let img = processor::decode_image(&image)?;
+    let sections: Vec<(Profile, palette::Palette)> = profiles.into_iter()
+        .map(|p| extractions.apply(p))
+        .map(|p| { let palette = processor::extract_palette_from_wallpaper(&img, &p); (p, palette) })
+        .collect();
+    preview::render(&img, &sections, &out)?;
+    println!("wrote {}", out.display());
+    Ok(())
+}
+
+/// `color-listener ctl <command> [args] [--socket path]`: talk to a running listener.
+fn run_ctl(args: &[String]) -> Result<()> {
+    let (_, file_config) = load_config(args)?;
+    let mut socket = file_config.socket.as_deref().map(expand_home).unwrap_or_else(control::default_socket_path);
+    let mut words: Vec<String> = Vec::new();
+    let mut args = args.iter().cloned();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => { flag_value(&mut args, &arg)?; }
+            "--socket" => { socket = expand_home(&flag_value(&mut args, &arg)?); }
+            _ => words.push(arg),
+        }
+    }
+    let reply = control::send(&socket, &control::Request::from_args(&words)?)?;
+    println!("{}", serde_json::to_string_pretty(&reply)?);
+    if reply["ok"] != serde_json::Value::Bool(true) { std::process::exit(1); }
+    Ok(())
+}
+
+/// `color-listener cache list|clear`
+fn run_cache(args: &[String]) -> Result<()> {
+    let cache = PaletteCache::new(PaletteCache::default_dir());
+    match args.first().map(String::as_str) {
+        Some("list") => {
+            for (key, written, entry) in cache.list()? {
+                let age = written.elapsed().map(|d| d.as_secs() / 60).unwrap_or(0);
+                println!("{key}  {}  bg={} accent={}  {}m ago  {}", entry.profile, entry.palette.background,
+                    entry.palette.accent, age, entry.wallpaper.display());
+            }
+            Ok(())
+        }
+        Some("clear") => {
+            let removed = cache.clear()?;
+            println!("removed {} cached palette(s) from {}", removed, cache.dir().display());
+            let blends = cache.clear_blends()?;
+            if blends > 0 { println!("removed {} blended wallpaper(s) from {}", blends, cache.blends_dir().display()); }
+            Ok(())
+        }
+        _ => Err(anyhow!("usage: color-listener cache list|clear")),
+    }
+}
+
+fn flag_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String> {
+    args.next().ok_or_else(|| anyhow!("{} needs a value", flag))
+}
+
+/// Record `applier.key=value` into the per-applier settings map.
+fn set_applier_setting(settings: &mut HashMap<String, ApplierSettings>, assignment: &str) -> Result<()> {
+    let (lhs, value) = assignment.split_once('=')
+        .ok_or_else(|| anyhow!("expected applier.key=value, got {}", assignment))?;
+    let (applier, key) = lhs.split_once('.')
+        .ok_or_else(|| anyhow!("expected applier.key=value, got {}", assignment))?;
+    settings.entry(applier.trim().to_lowercase()).or_default().insert(key.trim().to_string(), value.trim().to_string());
+    Ok(())
+}
+
+fn print_appliers(registry: &Registry) -> Result<()> {
+    let appliers = registry.resolve(&registry.names().iter().map(|n| n.to_string()).collect::<Vec<_>>(), &HashMap::new())?;
+    let defaults = registry.default_names();
+    for ap in appliers {
+        let note = if defaults.contains(&ap.name()) { "" } else { " (opt-in: enabled = true or --appliers)" };
+        println!("{}{}", ap.name(), note);
+        for key in ap.config_schema() {
+            println!("  {}.{} (default: {}) - {}", ap.name(), key.name, key.default, key.help);
+        }
+        println!("  {}.{} (default: true) - roll every target back when this applier fails", ap.name(), applier::REQUIRED_KEY);
+    }
+    Ok(())
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // defaults
-    let default_watchfile: &str = "~/.config/hypr/hyprpaper.conf";
     let default_colorfile: &str = "~/.config/dotfiles/colors.scss";
-    let default_ags_colorfile: &str = "~/.config/dotfiles/ags-4/colors.scss";
-    let default_hypr_colors: &str = "~/.config/dotfiles/hypr/colors.conf";
-    let default_kitty_conf: &str = "~/.config/dotfiles/kitty/kitty.conf";
-    let mut watcher = Watcher::Hyprpaper;
-    let mut appliers: Vec<Applier> = vec![Applier::Ags, Applier::Hyprland, Applier::Kitty, Applier::Ps1];
+    let registry = Registry::builtin();
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // One-shot subcommands
+    match args.first().map(String::as_str) {
+        Some("extract") => return run_extract(&args[1..]),
+        Some("preview") => return run_preview(&args[1..]),
+        Some("cache") => return run_cache(&args[1..]),
+        Some("ctl") => return run_ctl(&args[1..]),
+        _ => {}
+    }
+
+    // Config file first, so every CLI flag below can override it
+    let (config_path, file_config) = load_config(&args)?;
+    let sections = file_config.applier_sections()
+        .with_context(|| format!("invalid config {}", config_path.display()))?;
+
+    let mut watcher = match &file_config.watcher {
+        Some(name) => Watcher::from_str(name)
+            .ok_or_else(|| anyhow!("unknown watcher {} in {}", name, config_path.display()))?,
+        None => Watcher::Hyprpaper,
+    };
+    let mut profile = match &file_config.profile {
+        Some(name) => Profile::parse(name).with_context(|| format!("in {}", config_path.display()))?,
+        None => Profile::default(),
+    };
+    let schedule = file_config.schedule.as_ref().map(Schedule::from_config).transpose()
+        .with_context(|| format!("in {}", config_path.display()))?;
+    let mut profile_from_cli = false;
+    let mut extractions = Extractions::from_config(&file_config.extraction)
+        .with_context(|| format!("in {}", config_path.display()))?;
+    let mut applier_names: Vec<String> = registry.names().iter()
+        .filter(|n| registry.default_names().contains(n) || sections.enabled.iter().any(|e| e == *n))
+        .filter(|n| !sections.disabled.iter().any(|d| d == *n))
+        .map(|n| n.to_string())
+        .collect();
+    let mut applier_settings: HashMap<String, ApplierSettings> = sections.settings;
+    let mut use_cache = file_config.cache.unwrap_or(true);
+    let mut validation = Policy::parse(file_config.validation.as_deref().unwrap_or("warn"))
+        .with_context(|| format!("in {}", config_path.display()))?;
+    let mut socket = file_config.socket.as_deref().map(expand_home).unwrap_or_else(control::default_socket_path);
 
     // Working copies (expand ~ lazily after arg parsing)
-    let mut watchfile = default_watchfile.to_string();
-    let mut colorfile = default_colorfile.to_string();
-    let mut ags_colorfile = default_ags_colorfile.to_string();
-    let mut hypr_colors = default_hypr_colors.to_string();
-    let mut kitty_conf = default_kitty_conf.to_string();
-    let default_bashrc: &str = "~/.bashrc";
-    let mut bashrc = default_bashrc.to_string();
-
-    let mut args = env::args().skip(1);
+    let mut watchfile: Option<String> = file_config.watchfile.clone();
+    let mut monitor = MonitorChoice::parse(file_config.monitor.as_deref().unwrap_or("focused"));
+    let mut debounce = file_config.debounce.map(Duration::from_millis).unwrap_or(watchers::DEFAULT_DEBOUNCE);
+    let mut colorfile = file_config.colorfile.clone().unwrap_or_else(|| default_colorfile.to_string());
+
+    let mut args = args.into_iter();
     while let Some(arg) = args.next() {
+        // Legacy per-applier flags map onto registry settings.
+        let legacy = match arg.as_str() {
+            "--ags-colorfile" => Some("ags.colorfile"),
+            "--hypr-colors" => Some("hyprland.colors"),
+            "--kitty-conf" => Some("kitty.conf"),
+            "--bashrc" => Some("ps1.rc"),
+            _ => None,
+        };
+        if let Some(key) = legacy {
+            let val = flag_value(&mut args, &arg)?;
+            set_applier_setting(&mut applier_settings, &format!("{}={}", key, val))?;
+            continue;
+        }
         match arg.as_str() {
+            "--config" => { flag_value(&mut args, &arg)?; } // already loaded above
             "--watcher" => {
-                if let Some(val) = args.next() {
-                    if let Some(w) = Watcher::from_str(&val) { watcher = w; } else { eprintln!("Unknown watcher: {}", val); }
-                }
+                let val = flag_value(&mut args, &arg)?;
+                watcher = Watcher::from_str(&val).ok_or_else(|| anyhow!("unknown watcher: {}", val))?;
             }
             "--appliers" => {
-                if let Some(val) = args.next() {
-                    let parsed: Vec<Applier> = val
-                        .split(',')
-                        .filter_map(|s| Applier::from_str(s.trim()))
-                        .collect();
-                    if !parsed.is_empty() { appliers = parsed; }
-                    if appliers.is_empty() {
-                        eprintln!("No valid appliers specified in: {}", val);
-                    } else {
-                        // You can store `appliers` somewhere as needed
-                        println!("Using appliers: {:?}", appliers);
-                        // For now, just print or assign to a variable if you add it to main
-                    }
-                }
+                let val = flag_value(&mut args, &arg)?;
+                let parsed: Vec<String> = val
+                    .split(',')
+                    .map(|s| s.trim().to_lowercase())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+                if parsed.is_empty() { return Err(anyhow!("no valid appliers specified in: {}", val)); }
+                applier_names = parsed;
             }
-            "--watchfile" => { if let Some(val) = args.next() { watchfile = val; } }
-            "--colorfile" => { if let Some(val) = args.next() { colorfile = val; } }
-            "--ags-colorfile" => { if let Some(val) = args.next() { ags_colorfile = val; } }
-            "--hypr-colors" => { if let Some(val) = args.next() { hypr_colors = val; } }
-            "--kitty-conf" => { if let Some(val) = args.next() { kitty_conf = val; } }
-            "--bashrc" => { if let Some(val) = args.next() { bashrc = val; } }
-            _ => {}
+            "--profile" => { profile = Profile::parse(&flag_value(&mut args, &arg)?)?; profile_from_cli = true; }
+            "--extraction" => { extractions.force(Extraction::parse(&flag_value(&mut args, &arg)?)?); }
+            "--set" => { let val = flag_value(&mut args, &arg)?; set_applier_setting(&mut applier_settings, &val)?; }
+            "--no-cache" => { use_cache = false; }
+            "--validation" => { validation = Policy::parse(&flag_value(&mut args, &arg)?)?; }
+            "--socket" => { socket = expand_home(&flag_value(&mut args, &arg)?); }
+            "--list-appliers" => { return print_appliers(&registry); }
+            "--watchfile" => { watchfile = Some(flag_value(&mut args, &arg)?); }
+            "--monitor" => { monitor = MonitorChoice::parse(&flag_value(&mut args, &arg)?); }
+            "--debounce" => {
+                let val = flag_value(&mut args, &arg)?;
+                let ms: u64 = val.parse().map_err(|_| anyhow!("--debounce takes milliseconds, got {}", val))?;
+                debounce = Duration::from_millis(ms);
+            }
+            "--colorfile" => { colorfile = flag_value(&mut args, &arg)?; }
+            _ => return Err(anyhow!("unknown argument: {}", arg)),
         }
     }
 
+    // A schedule picks the starting profile; `--profile` still wins until its next boundary.
+    if let Some(schedule) = &schedule && !profile_from_cli {
+        profile = schedule.profile_at(Local::now()).clone();
+    }
+    let profile = extractions.apply(profile);
+
     // Expand tildes
-    watchfile = expand_tilde(&watchfile);
+    let watchfile = expand_tilde(&watchfile.unwrap_or_else(|| watcher.default_watchfile()));
     colorfile = expand_tilde(&colorfile);
-    ags_colorfile = expand_tilde(&ags_colorfile);
-    hypr_colors = expand_tilde(&hypr_colors);
-    kitty_conf = expand_tilde(&kitty_conf);
-    bashrc = expand_tilde(&bashrc);
+
+    let optional = applier::take_optional(&mut applier_settings)?;
+    let appliers = registry.resolve(&applier_names, &applier_settings)
+        .with_context(|| format!("checking applier settings (config: {})", config_path.display()))?;
 
     println!("Using watcher: {:?}", watcher);
     println!("Watching file: {}", watchfile);
+    if matches!(watcher, Watcher::Hyprpaper) { println!("Theme monitor: {}", monitor); }
+    println!("Debounce: {} ms", debounce.as_millis());
     println!("Color file: {}", colorfile);
-    println!("AGS color file: {}", ags_colorfile);
-    println!("Hypr colors file: {}", hypr_colors);
-    println!("Kitty conf: {}", kitty_conf);
-    println!("Bash rc: {}", bashrc);
-
-    // Start selected watcher and spawn receiver loop
-    match watcher {
-        Watcher::Hyprpaper => {
-            let rx = HyprpaperWatcher::new(&watchfile).start().await?;
-            let colorfile_path = colorfile.clone();
-            let ags_colorfile_path = ags_colorfile.clone();
-            let appliers_vec = appliers.clone();
-            let kitty_conf_path = kitty_conf.clone();
-            let bashrc_path = bashrc.clone();
+    println!("Profile: {}", profile.name);
+    println!("Extraction: {}", profile.extraction);
+    if let Some(schedule) = &schedule { println!("Schedule: {}", schedule.describe(Local::now())); }
+    if use_cache { println!("Palette cache: {}", PaletteCache::default_dir().display()); }
+    println!("Validation: {:?}", validation);
+    println!("Using appliers: {}", appliers.iter().map(|a| a.name()).collect::<Vec<_>>().join(", "));
+    if !optional.is_empty() { println!("Optional appliers: {}", optional.join(", ")); }
+
+    println!("Control socket: {}", socket.display());
+    let history_limit = file_config.history.unwrap_or(history::DEFAULT_HISTORY_LIMIT);
+    let history = History::load(&History::default_path(), history_limit);
+
+    let daemon = Arc::new(Mutex::new(Daemon::new(PathBuf::from(&colorfile), profile, appliers, optional, use_cache, history, validation)
+        .with_extractions(extractions)));
+    control::serve(&socket, daemon.clone()).await?;
+    if let Some(schedule) = schedule { tokio::spawn(schedule.run(daemon.clone())); }
+
+    // Start selected watcher and hand every new wallpaper to the daemon
+    let rx = watcher.start(&watchfile, monitor, debounce).await?;
+    let watcher_name = watcher.name();
+    tokio::spawn(async move {
+        let mut rx = rx;
+        let generations = Generations::default();
+        while let Some(mut ev) = rx.recv().await {
+            // Latest wins: anything queued behind this event replaces it.
+            while let Ok(newer) = rx.try_recv() { ev = newer; }
+            println!("[{}] new wallpaper: {}", watcher_name, ev.path.display());
+            // Keep receiving while the daemon works, so a newer wallpaper can cut older work short.
+            let generation = generations.issue();
+            let daemon = daemon.clone();
             tokio::spawn(async move {
-                let mut rx = rx;
-                while let Some(ev) = rx.recv().await {
-                    println!("[hyprpaper] new wallpaper: {} ({}x{})", ev.path.display(), ev.image.width(), ev.image.height());
-                    let proc = Processor::new(&colorfile_path, &ev.path);
-                    match proc.run() {
-                        Ok((p,s)) => {
-                            println!("[processor] wrote colors primary={} secondary={}", p,s);
-                            for ap in &appliers_vec {
-                                match ap {
-                                    Applier::Ags => {
-                                        if let Err(e) = applier::ags::apply(&ags_colorfile_path, &p, &s) {
-                                            eprintln!("[applier:ags] error: {e}");
-                                        } else {
-                                            println!("[applier:ags] updated {}", ags_colorfile_path);
-                                        }
-                                    }
-                                    Applier::Hyprland => {
-                                        if let Err(e) = applier::hyprland::apply(&hypr_colors, &p, &s) {
-                                            eprintln!("[applier:hyprland] error: {e}");
-                                        } else {
-                                            println!("[applier:hyprland] updated {}", hypr_colors);
-                                        }
-                                    }
-                                    Applier::Kitty => {
-                                        if let Err(e) = applier::kitty::apply(&kitty_conf_path, &p, &s) {
-                                            eprintln!("[applier:kitty] error: {e}");
-                                        } else {
-                                            println!("[applier:kitty] updated include in {}", kitty_conf_path);
-                                        }
-                                    }
-                                    Applier::Ps1 => {
-                                        if let Err(e) = applier::ps1::apply(&bashrc_path, &p, &s) {
-                                            eprintln!("[applier:ps1] error: {e}");
-                                        } else {
-                                            println!("[applier:ps1] updated PS1 theme and loader in {}", bashrc_path);
-                                        }
-                                    }
-                                }
-                            }
-                        },
-                        Err(e) => eprintln!("[processor] failed: {e}"),
-                    }
+                if let Err(e) = tokio::task::spawn_blocking(move || Daemon::on_wallpaper(&daemon, ev, &generation)).await {
+                    eprintln!("[daemon] {e}");
                 }
             });
         }
-    }
-    // when processor sends a color chnage signal, main spawns a task to handle it
-    // we use appliers here
-    
+    });
 
     // Wait for ctrl-c signal to exit
     tokio::signal::ctrl_c().await?;
+    let _ = std::fs::remove_file(&socket);
     Ok(())
 }
 
@@ -0,0 +1,73 @@
+// Palette produced by the processor and consumed by every applier.
+// All colors are `#RRGGBB` strings so appliers can format them however their target expects.
+
+use serde::{Deserialize, Serialize};
+
+use crate::color::hex_to_rgb;
+
+/// Shades of the accent color for hover/dim/emphasis states.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccentVariants {
+    pub dim: String,
+    pub muted: String,
+    pub bright: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Palette {
+    /// Tinted base: near-black on dark profiles, near-white on light ones (historically the "primary" color).
+    pub background: String,
+    /// Slightly lifted background for panels, selections and color0-ish surfaces.
+    pub surface: String,
+    /// Tinted text color, the opposite end from `background`.
+    pub foreground: String,
+    /// Accent (historically the "secondary" color).
+    pub accent: String,
+    pub accent_variants: AccentVariants,
+    /// Further accents from other distinct hues of the wallpaper, exported as `accent-2`,
+    /// `accent-3`, ... Only the clustering extractions fill it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accents: Vec<String>,
+    /// ANSI color0..color15 in terminal order.
+    pub ansi: [String; 16],
+}
+
+impl Palette {
+    /// Base color, the `$primary-color` every existing target already knows.
+    pub fn primary(&self) -> &str { &self.background }
+    /// Accent color, the `$secondary-color` every existing target already knows.
+    pub fn secondary(&self) -> &str { &self.accent }
+
+    /// Every named color in a stable order, e.g. for exports and previews.
+    pub fn named_colors(&self) -> Vec<(String, &str)> {
+        let mut out: Vec<(String, &str)> = vec![
+            ("background".to_string(), self.background.as_str()),
+            ("surface".to_string(), self.surface.as_str()),
+            ("foreground".to_string(), self.foreground.as_str()),
+            ("accent".to_string(), self.accent.as_str()),
+            ("accent-dim".to_string(), self.accent_variants.dim.as_str()),
+            ("accent-muted".to_string(), self.accent_variants.muted.as_str()),
+            ("accent-bright".to_string(), self.accent_variants.bright.as_str()),
+        ];
+        for (i, c) in self.accents.iter().enumerate() {
+            out.push((format!("accent-{}", i + 2), c.as_str()));
+        }
+        for (i, c) in self.ansi.iter().enumerate() {
+            out.push((format!("color{i}"), c.as_str()));
+        }
+        out
+    }
+
+    /// `$name: value;` per named color (`$accent`, `$accent-dim`, `$color1`, ...), the one
+    /// naming every SCSS target shares so widget snippets work against any of them.
+    /// `value` formats each hex, e.g. as `rgba()`.
+    pub fn scss_variables(&self, value: impl Fn(&str) -> String) -> String {
+        self.named_colors().iter().map(|(name, hex)| format!("${}: {};\n", name, value(hex))).collect()
+    }
+}
+
+/// `rgba(r, g, b, a)` for CSS/SCSS targets; unparsable hex becomes black.
+pub fn hex_to_css_rgba(hex: &str, alpha: f32) -> String {
+    let (r, g, b) = hex_to_rgb(hex).unwrap_or((0, 0, 0));
+    format!("rgba({}, {}, {}, {:.2})", r, g, b, alpha)
+}
@@ -0,0 +1,8 @@
+use std::path::PathBuf;
+
+/// Expand a leading `$HOME/` or `~/` to the user's home directory.
+pub fn expand_home(s: &str) -> PathBuf {
+    if let (Some(rest), Some(home)) = (s.strip_prefix("$HOME/"), std::env::var_os("HOME")) { return PathBuf::from(home).join(rest); }
+    if let (Some(rest), Some(home)) = (s.strip_prefix("~/"), std::env::var_os("HOME")) { return PathBuf::from(home).join(rest); }
+    PathBuf::from(s)
+}
@@ -0,0 +1,197 @@
+// PNG swatch sheet for `color-listener preview`: wallpaper thumbnail, then one section per
+// profile with the named colors, the ANSI grid and WCAG contrast ratios against the background.
+// Contrast below the profile target and washed-out accents are flagged with a `!` label.
+
+use anyhow::{Context, Result};
+use image::{imageops, DynamicImage, Rgb, RgbImage};
+use std::path::Path;
+
+use crate::color::{contrast_ratio_hex, hex_to_oklch, hex_to_rgb};
+use crate::palette::Palette;
+use crate::profile::Profile;
+
+const WIDTH: u32 = 1240;
+const MARGIN: u32 = 20;
+const THUMB_H: u32 = 270;
+const SECTION_H: u32 = 300;
+/// Extra height of a section whose palette has further accents (`accent-2`, ...), drawn on a
+/// row of their own below the named colors.
+const ACCENTS_ROW_H: u32 = 100;
+const SHEET_BG: Rgb<u8> = Rgb([0x30, 0x30, 0x30]);
+const WARN: Rgb<u8> = Rgb([0xFF, 0x55, 0x44]);
+/// Below this OkLCh chroma the accent reads as gray, which usually means the extraction failed.
+const LOW_ACCENT_CHROMA: f32 = 0.02;
+
+pub fn render(wallpaper: &DynamicImage, sections: &[(Profile, Palette)], out: &Path) -> Result<()> {
+    let height = MARGIN + THUMB_H + MARGIN + sections.iter().map(|(_, p)| section_height(p) + MARGIN).sum::<u32>();
+    let mut sheet = RgbImage::from_pixel(WIDTH, height, SHEET_BG);
+
+    let thumb = wallpaper.thumbnail(WIDTH - 2 * MARGIN, THUMB_H).to_rgb8();
+    imageops::overlay(&mut sheet, &thumb, MARGIN as i64, MARGIN as i64);
+
+    let mut y = MARGIN + THUMB_H + MARGIN;
+    for (profile, palette) in sections {
+        draw_section(&mut sheet, y, profile, palette);
+        y += section_height(palette) + MARGIN;
+    }
+    sheet.save(out).with_context(|| format!("write preview {}", out.display()))
+}
+
+fn section_height(palette: &Palette) -> u32 {
+    if palette.accents.is_empty() { SECTION_H } else { SECTION_H + ACCENTS_ROW_H }
+}
+
+fn draw_section(img: &mut RgbImage, top: u32, profile: &Profile, palette: &Palette) {
+    let bg = rgb(&palette.background);
+    fill_rect(img, MARGIN, top, WIDTH - 2 * MARGIN, section_height(palette), bg);
+
+    // Header: profile name plus a sample of foreground and accent text on the background
+    let x = MARGIN + 12;
+    draw_text(img, x, top + 12, &format!("PROFILE {}", profile.name), 3, rgb(&palette.foreground));
+    let accent_ratio = contrast_ratio_hex(&palette.accent, &palette.background);
+    let accent_label = format!("ACCENT {:.1}:1", accent_ratio);
+    draw_text(img, x + 420, top + 12, &accent_label, 3, rgb(&palette.accent));
+    let mut flags: Vec<&str> = Vec::new();
+    if accent_ratio < profile.min_contrast { flags.push("LOW CONTRAST"); }
+    if hex_to_oklch(&palette.accent).1 < LOW_ACCENT_CHROMA { flags.push("GRAY ACCENT"); }
+    if !flags.is_empty() {
+        draw_text(img, x + 800, top + 16, &format!("! {}", flags.join(" ")), 2, WARN);
+    }
+
+    // Named colors; further accents get a row of their own in the same columns
+    let named = palette.named_colors();
+    let (base, extra): (Vec<_>, Vec<_>) = named.into_iter()
+        .filter(|(n, _)| !n.starts_with("color"))
+        .partition(|(n, _)| !is_extra_accent(n));
+    let cell_w = (WIDTH - 2 * MARGIN - 24) / base.len() as u32;
+    for (row, colors) in [base, extra].iter().enumerate() {
+        for (i, (name, hex)) in colors.iter().enumerate() {
+            let cx = x + i as u32 * cell_w;
+            swatch(img, cx, top + 48 + row as u32 * ACCENTS_ROW_H, cell_w - 8, 90, name, hex, &palette.background, profile.min_contrast);
+        }
+    }
+
+    // ANSI 2x8 grid
+    let ansi_top = top + 150 + section_height(palette) - SECTION_H;
+    let cell_w = (WIDTH - 2 * MARGIN - 24) / 8;
+    for (i, hex) in palette.ansi.iter().enumerate() {
+        let (row, col) = (i as u32 / 8, i as u32 % 8);
+        let cx = x + col * cell_w;
+        let cy = ansi_top + row * 72;
+        swatch(img, cx, cy, cell_w - 8, 64, &format!("color{i}"), hex, &palette.background, profile.min_contrast);
+    }
+}
+
+// `accent-2`, `accent-3`, ... as opposed to the accent's own variants (`accent-dim`, ...).
+fn is_extra_accent(name: &str) -> bool {
+    name.strip_prefix("accent-").is_some_and(|n| n.parse::<usize>().is_ok())
+}
+
+/// Filled box with name, hex and contrast against `background`; `!` when below `min_contrast`.
+#[allow(clippy::too_many_arguments)]
+fn swatch(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32, name: &str, hex: &str, background: &str, min_contrast: f32) {
+    let fill = rgb(hex);
+    fill_rect(img, x, y, w, h, fill);
+    outline(img, x, y, w, h, Rgb([0x80, 0x80, 0x80]));
+    let ink = readable_ink(hex);
+    // Names longer than the box are cut rather than drawn over the next swatch.
+    let fits = ((w.saturating_sub(8)) / (6 * 2)) as usize;
+    let name: String = name.chars().take(fits).collect();
+    draw_text(img, x + 6, y + 6, &name, 2, ink);
+    draw_text(img, x + 6, y + 24, hex, 2, ink);
+    if hex == background { return; }
+    let ratio = contrast_ratio_hex(hex, background);
+    let mark = if ratio < min_contrast { " !" } else { "" };
+    draw_text(img, x + 6, y + h - 20, &format!("{:.1}:1{}", ratio, mark), 2, ink);
+}
+
+fn rgb(hex: &str) -> Rgb<u8> {
+    let (r, g, b) = hex_to_rgb(hex).unwrap_or((0, 0, 0));
+    Rgb([r, g, b])
+}
+
+/// Black or white, whichever contrasts more with `hex`.
+fn readable_ink(hex: &str) -> Rgb<u8> {
+    if contrast_ratio_hex(hex, "#000000") >= contrast_ratio_hex(hex, "#FFFFFF") { Rgb([0, 0, 0]) } else { Rgb([0xFF, 0xFF, 0xFF]) }
+}
+
+fn fill_rect(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32, color: Rgb<u8>) {
+    for py in y..(y + h).min(img.height()) {
+        for px in x..(x + w).min(img.width()) {
+            img.put_pixel(px, py, color);
+        }
+    }
+}
+
+fn outline(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32, color: Rgb<u8>) {
+    fill_rect(img, x, y, w, 1, color);
+    fill_rect(img, x, y + h - 1, w, 1, color);
+    fill_rect(img, x, y, 1, h, color);
+    fill_rect(img, x + w - 1, y, 1, h, color);
+}
+
+// ------------------------------ 5x7 font -----------------------------------
+
+fn draw_text(img: &mut RgbImage, x: u32, y: u32, text: &str, scale: u32, color: Rgb<u8>) {
+    let mut cx = x;
+    for ch in text.chars() {
+        let rows = glyph(ch.to_ascii_uppercase());
+        for (ry, bits) in rows.iter().enumerate() {
+            for rx in 0..5u32 {
+                if bits & (0x10 >> rx) != 0 {
+                    fill_rect(img, cx + rx * scale, y + ry as u32 * scale, scale, scale, color);
+                }
+            }
+        }
+        cx += 6 * scale;
+    }
+}
+
+/// Rows top to bottom, bit 4 is the leftmost column. Unknown characters render as blanks.
+fn glyph(c: char) -> [u8; 7] {
+    match c {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '#' => [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '/' => [0x01, 0x01, 0x02, 0x04, 0x08, 0x10, 0x10],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        _ => [0; 7],
+    }
+}
@@ -1,38 +1,74 @@
-// Cargo.toml
-// [dependencies]
-// image = "0.24"
-// anyhow = "1.0"
-
 use anyhow::{Context, Result};
 use image::{DynamicImage, ImageReader};
 use std::f32::consts::PI;
 use std::path::{Path, PathBuf};
 
+use crate::color::{
+    contrast_ratio_hex, hex_to_oklch, hex_to_rgb, hue_delta, oklch_to_hex_gamut_ok, relative_luminance,
+    srgb8_to_oklab, to_hex,
+};
+use crate::cache::PaletteCache;
+use crate::cluster;
+use crate::palette::{AccentVariants, Palette};
+use crate::profile::Profile;
+
 pub struct Processor {
-    pub colorfile: PathBuf,
     pub wallpaper_path: PathBuf,
+    pub profile: Profile,
+    pub cache: Option<PaletteCache>,
 }
 
 impl Processor {
-    pub fn new<C: AsRef<Path>, W: AsRef<Path>>(colorfile: C, wallpaper_path: W) -> Self {
-        Self { colorfile: colorfile.as_ref().to_path_buf(),
-               wallpaper_path: wallpaper_path.as_ref().to_path_buf() }
+    pub fn new<W: AsRef<Path>>(wallpaper_path: W, profile: Profile) -> Self {
+        Self { wallpaper_path: wallpaper_path.as_ref().to_path_buf(),
+               profile,
+               cache: None }
     }
 
-    /// Extract and write. Returns (primary, secondary) hex.
-    pub fn run(&self) -> Result<(String, String)> {
-        let img = ImageReader::open(&self.wallpaper_path)
-            .with_context(|| format!("open image {}", self.wallpaper_path.display()))?
-            .with_guessed_format()?
-            .decode()
-            .with_context(|| "decode image")?;
-
-        let (primary, secondary) = extract_pair_from_wallpaper(&img);
-        write_colors_file(&self.colorfile, &primary, &secondary)?;
-        Ok((primary, secondary))
+    /// Look palettes up in (and store them to) `cache` before extracting.
+    pub fn with_cache(mut self, cache: PaletteCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Palette for the already decoded wallpaper, from the cache when possible.
+    /// Writing it out is up to the caller (see `write_colors_file`).
+    pub fn palette(&self, img: &DynamicImage) -> Palette {
+        match &self.cache {
+            Some(cache) => self.cached_palette(cache, img),
+            None => extract_palette_from_wallpaper(img, &self.profile),
+        }
+    }
+
+    /// Cache problems never fail a run; they only cost a recompute.
+    fn cached_palette(&self, cache: &PaletteCache, img: &DynamicImage) -> Palette {
+        let key = match PaletteCache::key(&self.wallpaper_path, &self.profile) {
+            Ok(k) => k,
+            Err(e) => {
+                eprintln!("[cache] {e}");
+                return extract_palette_from_wallpaper(img, &self.profile);
+            }
+        };
+        if let Some(palette) = cache.get(&key) {
+            println!("[cache] hit {key}");
+            return palette;
+        }
+        let palette = extract_palette_from_wallpaper(img, &self.profile);
+        if let Err(e) = cache.put(&key, &self.wallpaper_path, &self.profile, &palette) {
+            eprintln!("[cache] store {key} failed: {e}");
+        }
+        palette
     }
 }
 
+pub fn decode_image(path: &Path) -> Result<DynamicImage> {
+    ImageReader::open(path)
+        .with_context(|| format!("open image {}", path.display()))?
+        .with_guessed_format()?
+        .decode()
+        .with_context(|| "decode image")
+}
+
 // ----------------------- Tunables & algorithm ------------------------------
 
 const MAX_THUMB: u32 = 256;
@@ -41,112 +77,229 @@ const MIN_C: f32 = 0.030;                   // ignore near-gray
 const CENTER_SIGMA_FRAC: f32 = 0.38;
 const C_WEIGHT_GAMMA: f32 = 1.25;
 
-// Primary target (tinted black)
-const L_PRIMARY: f32       = 0.20;
-const C_PRIMARY_MIN: f32   = 0.030;
-const C_PRIMARY_MAX: f32   = 0.110;
-const PRIMARY_LUMA_MAX: f32   = 0.11;
-const PRIMARY_MAX_CHANNEL: u8 = 0x22;
-
-// Secondary “pastel clamp” (prevents whiteness)
-const L2_TARGET: f32       = 0.82;   // base lightness
-const L2_MIN: f32          = 0.78;   // never whiter than this band
-const L2_MAX: f32          = 0.86;
-const C2_MIN: f32          = 0.08;   // more saturation than before
-const C2_MAX: f32          = 0.18;
-const MIN_CONTRAST: f32    = 3.0;
-
-fn extract_pair_from_wallpaper(img: &DynamicImage) -> (String, String) {
-    let rgb = img.thumbnail(MAX_THUMB, MAX_THUMB).to_rgb8();
-    let (w, h) = rgb.dimensions();
-    let (cx, cy) = (w as f32 / 2.0, h as f32 / 2.0);
-    let sigma = CENTER_SIGMA_FRAC * (w.max(h) as f32 / 2.0);
-    let inv2s2 = 1.0 / (2.0 * sigma * sigma).max(1e-6);
-
-    // weighted hue histogram
-    let mut bin_w = vec![0f32; HUE_BINS];
-    let mut bin_sin = vec![0f32; HUE_BINS];
-    let mut bin_cos = vec![0f32; HUE_BINS];
-    let mut bin_csum = vec![0f32; HUE_BINS];
-
-    for (y, row) in rgb.rows().enumerate() {
-        for (x, p) in row.enumerate() {
-            let (r, g, b) = (p[0], p[1], p[2]);
-            let (_l, a, bb) = srgb8_to_oklab(r, g, b); // _l unused (we target custom L bands)
-            let c = (a * a + bb * bb).sqrt();
-            if c < MIN_C { continue; }
-
-            let dx = x as f32 - cx;
-            let dy = y as f32 - cy;
-            let focal = (- (dx*dx + dy*dy) * inv2s2).exp();
-            let weight = focal * c.powf(C_WEIGHT_GAMMA);
-
-            let hue = bb.atan2(a);
-            let bin = hue_to_bin(hue);
-            bin_w[bin]   += weight;
-            bin_sin[bin] += weight * hue.sin();
-            bin_cos[bin] += weight * hue.cos();
-            bin_csum[bin]+= weight * c;
+// Lightness/chroma bands and contrast live in `Profile`; these shape the histogram only.
+// The clustering extractions (`Profile::extraction`) live in cluster.rs.
+
+// ANSI hues (OkLCh degrees) for red, green, yellow, blue, magenta, cyan.
+// Each is pulled towards the strongest nearby wallpaper hue so the set feels related to the image.
+const ANSI_BASE_HUES_DEG: [f32; 6] = [29.0, 142.0, 100.0, 264.0, 328.0, 195.0];
+const ANSI_PULL_WINDOW_DEG: f32 = 25.0;  // only hues this close can pull
+const ANSI_PULL: f32       = 0.5;    // fraction of the distance to move towards that hue
+const ANSI_MIN_SHARE: f32  = 0.02;   // nearby hue must hold this share of the histogram
+
+/// Center-weighted hue histogram in OkLab; the common input of every palette step.
+struct HueHistogram {
+    weight: Vec<f32>,
+    sin: Vec<f32>,
+    cos: Vec<f32>,
+    chroma: Vec<f32>,
+}
+
+impl HueHistogram {
+    fn empty() -> Self {
+        Self {
+            weight: vec![0f32; HUE_BINS],
+            sin: vec![0f32; HUE_BINS],
+            cos: vec![0f32; HUE_BINS],
+            chroma: vec![0f32; HUE_BINS],
         }
     }
 
-    // neutral fallback
-    if bin_w.iter().copied().sum::<f32>() < 1e-4 {
-        let primary = "#141414".to_string();
-        let secondary = "#E5E5E5".to_string();
-        return (primary, secondary);
+    fn build(img: &DynamicImage) -> Self {
+        let rgb = img.thumbnail(MAX_THUMB, MAX_THUMB).to_rgb8();
+        let (w, h) = rgb.dimensions();
+        let (cx, cy) = (w as f32 / 2.0, h as f32 / 2.0);
+        let sigma = CENTER_SIGMA_FRAC * (w.max(h) as f32 / 2.0);
+        let inv2s2 = 1.0 / (2.0 * sigma * sigma).max(1e-6);
+
+        let mut hist = Self::empty();
+
+        for (y, row) in rgb.rows().enumerate() {
+            for (x, p) in row.enumerate() {
+                let (r, g, b) = (p[0], p[1], p[2]);
+                let (_l, a, bb) = srgb8_to_oklab(r, g, b); // _l unused (we target custom L bands)
+                let c = (a * a + bb * bb).sqrt();
+                if c < MIN_C { continue; }
+
+                let dx = x as f32 - cx;
+                let dy = y as f32 - cy;
+                let focal = (- (dx*dx + dy*dy) * inv2s2).exp();
+                let weight = focal * c.powf(C_WEIGHT_GAMMA);
+
+                let hue = bb.atan2(a);
+                let bin = hue_to_bin(hue);
+                hist.weight[bin] += weight;
+                hist.sin[bin]    += weight * hue.sin();
+                hist.cos[bin]    += weight * hue.cos();
+                hist.chroma[bin] += weight * c;
+            }
+        }
+        hist
+    }
+
+    fn total(&self) -> f32 { self.weight.iter().copied().sum() }
+
+    fn best_bin(&self) -> usize {
+        self.weight.iter().enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap_or(0)
     }
 
-    // best hue (windowed mean)
-    let (best_idx, _) = bin_w.iter().enumerate()
-        .max_by(|a,b| a.1.partial_cmp(b.1).unwrap()).unwrap();
-
-    let mut sum_w = 0.0;
-    let mut sum_s = 0.0;
-    let mut sum_c = 0.0;
-    let mut sum_cs = 0.0;
-    for off in [-1isize, 0, 1] {
-        let i = wrap_bin(best_idx as isize + off);
-        sum_w += bin_w[i];
-        sum_s += bin_sin[i];
-        sum_c += bin_cos[i];
-        sum_cs += bin_csum[i];
+    /// Circular mean hue and average chroma of the bins within `radius` of `center`.
+    fn window(&self, center: usize, radius: isize) -> (f32, f32) {
+        let (mut sum_w, mut sum_s, mut sum_c, mut sum_cs) = (0.0, 0.0, 0.0, 0.0);
+        for off in -radius..=radius {
+            let i = wrap_bin(center as isize + off);
+            sum_w += self.weight[i];
+            sum_s += self.sin[i];
+            sum_c += self.cos[i];
+            sum_cs += self.chroma[i];
+        }
+        let hue = sum_s.atan2(sum_c);
+        let avg_c = if sum_w > 0.0 { (sum_cs / sum_w).clamp(0.0, 0.30) } else { 0.12 };
+        (hue, avg_c)
     }
-    let hue = sum_s.atan2(sum_c);
-    let avg_c = if sum_w > 0.0 { (sum_cs / sum_w).clamp(0.0, 0.30) } else { 0.12 };
+}
+
+/// Palette for an already decoded image, without writing anything.
+pub fn extract_palette_from_wallpaper(img: &DynamicImage, profile: &Profile) -> Palette {
+    let hist = HueHistogram::build(img);
+
+    // neutral fallback
+    let total = hist.total();
+    if total < 1e-4 {
+        return neutral_palette(&hist, profile);
+    }
+
+    // best hue (windowed mean), or the strongest cluster and any further distinct ones
+    let (hue, avg_c, others) = match cluster::accent_hues(img, profile.extraction).split_first() {
+        Some((&(hue, avg_c), rest)) => (hue, avg_c, rest.to_vec()),
+        None => {
+            let (hue, avg_c) = hist.window(hist.best_bin(), 1);
+            (hue, avg_c, Vec::new())
+        }
+    };
 
-    // PRIMARY — roll chroma down for a near-black tint
-    let c_primary = ((avg_c * 0.55) + 0.03).clamp(C_PRIMARY_MIN, C_PRIMARY_MAX);
-    let mut primary   = oklch_to_hex_gamut_ok(L_PRIMARY, c_primary, hue);
-    primary = enforce_ultra_dark_primary(&primary);
+    // PRIMARY — roll chroma down for a tinted base (near-black for dark profiles)
+    let c_primary = ((avg_c * 0.55) + 0.03).clamp(profile.c_background_min, profile.c_background_max);
+    let mut primary   = oklch_to_hex_gamut_ok(profile.l_background, c_primary, hue);
+    primary = enforce_ultra_dark_primary(&primary, profile);
 
-    // SECONDARY — pastel (light but not white)
-    let (l2, c2, h2) = shape_secondary_pastel(L2_TARGET, avg_c, hue);
-    let mut secondary = oklch_to_hex_gamut_ok(l2, c2, h2);
+    // SECONDARY — pastel (light but not white) on dark profiles, deep accent on light ones
+    let secondary = shape_accent(&primary, avg_c, hue, profile);
+    let accents = others.iter().map(|&(h, c)| shape_accent(&primary, c, h, profile)).collect();
+
+    let surface = oklch_to_hex_gamut_ok(profile.l_surface, (c_primary * 0.6).max(profile.c_neutral), hue);
+    let foreground = oklch_to_hex_gamut_ok(profile.l_foreground, profile.c_neutral, hue);
+    let accent_variants = accent_variants(&secondary, profile);
+    let ansi = build_ansi(&hist, total, hue, avg_c, profile.c_neutral, &primary, &foreground, profile);
+
+    Palette { background: primary, surface, foreground, accent: secondary, accent_variants, accents, ansi }
+}
+
+/// Accent of `hue` on `background`, shaped into the profile's band and held to its contrast.
+fn shape_accent(background: &str, avg_c: f32, hue: f32, profile: &Profile) -> String {
+    let (l2, c2, h2) = shape_secondary_pastel(profile, avg_c, hue);
+    let mut accent = oklch_to_hex_gamut_ok(l2, c2, h2);
 
     // ensure contrast without bleaching: try a bit more chroma first, then tiny lightness nudge inside clamp
+    let l_step = if profile.dark { 0.005 } else { -0.005 };
     for _ in 0..6 {
-        if contrast_ratio_hex(&primary, &secondary) >= MIN_CONTRAST { break; }
-        let (mut l, mut c, h) = hex_to_oklch(&secondary);
-        c = (c * 1.06).clamp(C2_MIN, C2_MAX);
-        l = (l + 0.005).clamp(L2_MIN, L2_MAX);
-        secondary = oklch_to_hex_gamut_ok(l, c, h);
+        if contrast_ratio_hex(background, &accent) >= profile.min_contrast { break; }
+        let (mut l, mut c, h) = hex_to_oklch(&accent);
+        c = (c * 1.06).clamp(profile.c_accent_min, profile.c_accent_max);
+        l = (l + l_step).clamp(profile.l_accent_min, profile.l_accent_max);
+        accent = oklch_to_hex_gamut_ok(l, c, h);
     }
+    accent
+}
 
-    (primary, secondary)
+/// Full palette around a hand-picked background/accent pair (`set-palette`). Both are kept
+/// verbatim; neutrals and ANSI are tinted by the accent hue as if it were the wallpaper's.
+pub fn palette_from_colors(background: &str, accent: &str, profile: &Profile) -> Palette {
+    let (_, c_background, _) = hex_to_oklch(background);
+    let (_, c_accent, hue) = hex_to_oklch(accent);
+    let surface = oklch_to_hex_gamut_ok(profile.l_surface, (c_background * 0.6).max(profile.c_neutral), hue);
+    let foreground = oklch_to_hex_gamut_ok(profile.l_foreground, profile.c_neutral, hue);
+    let accent_variants = accent_variants(accent, profile);
+    let ansi = build_ansi(&HueHistogram::empty(), 0.0, hue, c_accent, profile.c_neutral, background, &foreground, profile);
+    Palette { background: background.to_string(), surface, foreground, accent: accent.to_string(), accent_variants, accents: Vec::new(), ansi }
 }
 
-// Pastel shaper: clamp L and C to a “milky but colored” band
-fn shape_secondary_pastel(l_target: f32, avg_c: f32, hue: f32) -> (f32, f32, f32) {
+// Pastel shaper: clamp L and C to the profile's “milky but colored” band
+fn shape_secondary_pastel(profile: &Profile, avg_c: f32, hue: f32) -> (f32, f32, f32) {
     // Give it some of the image’s chroma so it doesn’t look gray/white.
-    let desired_c = (avg_c * 0.70 + 0.06).clamp(C2_MIN, C2_MAX);
-    let l = l_target.clamp(L2_MIN, L2_MAX);
+    let desired_c = (avg_c * 0.70 + 0.06).clamp(profile.c_accent_min, profile.c_accent_max);
+    let l = profile.l_accent_target.clamp(profile.l_accent_min, profile.l_accent_max);
     (l, desired_c, hue)
 }
 
+/// Dim/muted move towards the background, bright away from it.
+pub(crate) fn accent_variants(accent: &str, profile: &Profile) -> AccentVariants {
+    let (l, c, h) = hex_to_oklch(accent);
+    let towards_bg = if profile.dark { -1.0 } else { 1.0 };
+    AccentVariants {
+        dim: oklch_to_hex_gamut_ok((l + towards_bg * 0.16).clamp(0.0, 1.0), c * 0.90, h),
+        muted: oklch_to_hex_gamut_ok((l + towards_bg * 0.06).clamp(0.0, 1.0), c * 0.45, h),
+        bright: oklch_to_hex_gamut_ok((l - towards_bg * 0.08).clamp(0.03, 0.97), (c * 1.10).min(profile.c_accent_max * 1.2), h),
+    }
+}
+
+/// ANSI 0..15: grays tinted by `tint` chroma of the dominant hue, six hue-pulled chromatic slots, brighter twins.
+/// color0/color15 stay the darkest/lightest ends regardless of which one is the background.
+#[allow(clippy::too_many_arguments)]
+fn build_ansi(hist: &HueHistogram, total: f32, hue: f32, avg_c: f32, tint: f32, background: &str, foreground: &str, profile: &Profile) -> [String; 16] {
+    let c_base = (avg_c * 0.8 + 0.05).clamp(profile.ansi_c_min, profile.ansi_c_max);
+    let (darkest, lightest) = if profile.dark { (background, foreground) } else { (foreground, background) };
+    let mut normal: Vec<String> = Vec::with_capacity(8);
+    let mut bright: Vec<String> = Vec::with_capacity(8);
+
+    normal.push(darkest.to_string());
+    bright.push(oklch_to_hex_gamut_ok(0.45, tint, hue));
+    for base_deg in ANSI_BASE_HUES_DEG {
+        let h = pull_hue_towards_wallpaper(hist, total, base_deg.to_radians());
+        normal.push(oklch_to_hex_gamut_ok(profile.ansi_l_normal, c_base, h));
+        bright.push(oklch_to_hex_gamut_ok(profile.ansi_l_bright, (c_base * 1.1).min(profile.ansi_c_max), h));
+    }
+    normal.push(oklch_to_hex_gamut_ok(0.80, tint, hue));
+    bright.push(lightest.to_string());
+
+    let mut out: [String; 16] = Default::default();
+    for (i, c) in normal.into_iter().chain(bright).enumerate() { out[i] = c; }
+    out
+}
+
+fn pull_hue_towards_wallpaper(hist: &HueHistogram, total: f32, base: f32) -> f32 {
+    let window = (ANSI_PULL_WINDOW_DEG / (360.0 / HUE_BINS as f32)).round() as isize;
+    let center = hue_to_bin(base) as isize;
+    let strongest = (-window..=window)
+        .map(|off| wrap_bin(center + off))
+        .max_by(|a, b| hist.weight[*a].total_cmp(&hist.weight[*b]));
+    let Some(bin) = strongest else { return base };
+    if total <= 0.0 || hist.weight[bin] < total * ANSI_MIN_SHARE { return base; }
+    let (peak, _) = hist.window(bin, 0);
+    base + hue_delta(base, peak) * ANSI_PULL
+}
+
+/// Gray palette for images without meaningful chroma; ANSI keeps standard hues so it stays usable.
+fn neutral_palette(hist: &HueHistogram, profile: &Profile) -> Palette {
+    let gray = |l: f32| oklch_to_hex_gamut_ok(l, 0.0, 0.0);
+    let (primary, secondary) = if profile.dark {
+        ("#141414".to_string(), "#E5E5E5".to_string())
+    } else {
+        (gray(profile.l_background), gray(profile.l_accent_target))
+    };
+    let foreground = gray(profile.l_foreground);
+    let accent_variants = accent_variants(&secondary, profile);
+    let ansi = build_ansi(hist, 0.0, 0.0, 0.0, 0.0, &primary, &foreground, profile);
+    Palette { background: primary, surface: gray(profile.l_surface), foreground, accent: secondary, accent_variants, accents: Vec::new(), ansi }
+}
+
 // ---------------------------- I/O helpers ----------------------------------
 
-fn write_colors_file(path: &Path, primary: &str, secondary: &str) -> Result<()> {
+/// `$primary-color`/`$secondary-color` plus every named color as SCSS variables.
+pub fn write_colors_file(path: &Path, palette: &Palette) -> Result<()> {
     use std::fs;
     use std::io::Write;
     let tmp = path.with_extension("tmp");
@@ -154,13 +307,12 @@ fn write_colors_file(path: &Path, primary: &str, secondary: &str) -> Result<()>
     let to_rgba = |hex: &str| -> String {
         if let Some((r,g,b)) = hex_to_rgb(hex) { format!("rgba({},{},{},1.0)", r,g,b) } else { hex.to_string() }
     };
-    let primary_rgba = to_rgba(primary);
-    let secondary_rgba = to_rgba(secondary);
-    let contents = format!(
+    let mut contents = format!(
         "$primary-color: {p};\n$secondary-color: {s};\n",
-        p = primary_rgba,
-        s = secondary_rgba
+        p = to_rgba(palette.primary()),
+        s = to_rgba(palette.secondary())
     );
+    contents.push_str(&palette.scss_variables(to_rgba));
     {
         let mut f = fs::File::create(&tmp)?;
         f.write_all(contents.as_bytes())?;
@@ -170,9 +322,10 @@ fn write_colors_file(path: &Path, primary: &str, secondary: &str) -> Result<()>
     Ok(())
 }
 
-// ----------------------- Color math / conversions --------------------------
+// ------------------------------ Helpers ------------------------------------
 
 fn hue_to_bin(h: f32) -> usize {
+    let h = h.sin().atan2(h.cos()); // wrap into (-PI, PI]
     let norm = (h + PI) / (2.0 * PI);
     (norm * HUE_BINS as f32).floor().clamp(0.0, (HUE_BINS - 1) as f32) as usize
 }
@@ -180,116 +333,18 @@ fn wrap_bin(i: isize) -> usize {
     let n = HUE_BINS as isize; let mut k = i % n; if k < 0 { k += n; } k as usize
 }
 
-fn srgb_to_linear(x: f32) -> f32 {
-    if x <= 0.04045 { x / 12.92 } else { ((x + 0.055) / 1.055).powf(2.4) }
-}
-fn linear_to_srgb(x: f32) -> f32 {
-    if x <= 0.0031308 { 12.92 * x } else { 1.055 * x.powf(1.0/2.4) - 0.055 }
-}
-
-fn srgb8_to_oklab(r: u8, g: u8, b: u8) -> (f32,f32,f32) {
-    let r = srgb_to_linear(r as f32 / 255.0);
-    let g = srgb_to_linear(g as f32 / 255.0);
-    let b = srgb_to_linear(b as f32 / 255.0);
-
-    let l = 0.4122214708*r + 0.5363325363*g + 0.0514459929*b;
-    let m = 0.2119034982*r + 0.6806995451*g + 0.1073969566*b;
-    let s = 0.0883024619*r + 0.2817188376*g + 0.6299787005*b;
-
-    let l_ = l.cbrt(); let m_ = m.cbrt(); let s_ = s.cbrt();
-
-    let L = 0.2104542553*l_ + 0.7936177850*m_ - 0.0040720468*s_;
-    let A = 1.9779984951*l_ - 2.4285922050*m_ + 0.4505937099*s_;
-    let B = 0.0259040371*l_ + 0.7827717662*m_ - 0.8086757660*s_;
-    (L, A, B)
-}
-
-fn oklab_to_srgb(l: f32, a: f32, b: f32) -> (f32,f32,f32) {
-    let l_ = l + 0.3963377774*a + 0.2158037573*b;
-    let m_ = l - 0.1055613458*a - 0.0638541728*b;
-    let s_ = l - 0.0894841775*a - 1.2914855480*b;
-
-    let l3 = l_*l_*l_;
-    let m3 = m_*m_*m_;
-    let s3 = s_*s_*s_;
-
-    let r =  4.0767416621*l3 - 3.3077115913*m3 + 0.2309699292*s3;
-    let g = -1.2684380046*l3 + 2.6097574011*m3 - 0.3413193965*s3;
-    let b = -0.0041960863*l3 - 0.7034186147*m3 + 1.7076147010*s3;
-
-    (linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b))
-}
-
-fn oklch_to_hex_gamut_ok(l: f32, mut c: f32, h: f32) -> String {
-    for _ in 0..12 {
-        let a = c * h.cos();
-        let b = c * h.sin();
-        let (r, g, bb) = oklab_to_srgb(l, a, b);
-        if (0.0..=1.0).contains(&r) && (0.0..=1.0).contains(&g) && (0.0..=1.0).contains(&bb) {
-            return to_hex((r*255.0).round() as u8,
-                          (g*255.0).round() as u8,
-                          (bb*255.0).round() as u8);
-        }
-        c *= 0.88; // shrink chroma until inside sRGB
-    }
-    let a = c * h.cos(); let b = c * h.sin();
-    let (r, g, bb) = oklab_to_srgb(l, a, b);
-    to_hex((r.clamp(0.0,1.0)*255.0).round() as u8,
-           (g.clamp(0.0,1.0)*255.0).round() as u8,
-           (bb.clamp(0.0,1.0)*255.0).round() as u8)
-}
-
-fn hex_to_oklch(hex: &str) -> (f32,f32,f32) {
-    let (r,g,b) = hex_to_rgb(hex).unwrap_or((127,127,127));
-    let (l,a,b2) = srgb8_to_oklab(r,g,b);
-    let c = (a*a + b2*b2).sqrt();
-    let h = b2.atan2(a);
-    (l,c,h)
-}
-
-fn to_hex(r:u8,g:u8,b:u8)->String { format!("#{:02X}{:02X}{:02X}", r,g,b) }
-
-fn hex_to_rgb(hex: &str) -> Option<(u8,u8,u8)> {
-    if hex.len()==7 && hex.starts_with('#') {
-        Some((
-            u8::from_str_radix(&hex[1..3],16).ok()?,
-            u8::from_str_radix(&hex[3..5],16).ok()?,
-            u8::from_str_radix(&hex[5..7],16).ok()?
-        ))
-    } else { None }
-}
-
-fn relative_luminance(rgb: (u8,u8,u8)) -> f32 {
-    fn lin(c: u8) -> f32 {
-        let x = c as f32 / 255.0;
-        if x <= 0.04045 { x / 12.92 } else { ((x + 0.055)/1.055).powf(2.4) }
-    }
-    let (r,g,b) = rgb;
-    0.2126*lin(r) + 0.7152*lin(g) + 0.0722*lin(b)
-}
-fn contrast_ratio(a: (u8,u8,u8), b: (u8,u8,u8)) -> f32 {
-    let la = relative_luminance(a);
-    let lb = relative_luminance(b);
-    let (hi, lo) = if la > lb { (la, lb) } else { (lb, la) };
-    (hi + 0.05) / (lo + 0.05)
-}
-fn contrast_ratio_hex(a: &str, b: &str) -> f32 {
-    let ar = hex_to_rgb(a).unwrap();
-    let br = hex_to_rgb(b).unwrap();
-    contrast_ratio(ar, br)
-}
-
-fn enforce_ultra_dark_primary(hex: &str) -> String {
+/// Clamp dark backgrounds to the profile's luminance/channel caps; light profiles pass through.
+fn enforce_ultra_dark_primary(hex: &str, profile: &Profile) -> String {
     if let Some((mut r, mut g, mut b)) = hex_to_rgb(hex) {
-        if relative_luminance((r,g,b)) > PRIMARY_LUMA_MAX {
-            let scale = (PRIMARY_LUMA_MAX / relative_luminance((r,g,b))).min(1.0) * 0.95;
+        if let Some(luma_max) = profile.background_luma_max.filter(|m| relative_luminance((r,g,b)) > *m) {
+            let scale = (luma_max / relative_luminance((r,g,b))).min(1.0) * 0.95;
             r = (r as f32 * scale).round().clamp(0.0, 255.0) as u8;
             g = (g as f32 * scale).round().clamp(0.0, 255.0) as u8;
             b = (b as f32 * scale).round().clamp(0.0, 255.0) as u8;
         }
         let maxc = r.max(g).max(b);
-        if maxc > PRIMARY_MAX_CHANNEL {
-            let rescale = (PRIMARY_MAX_CHANNEL as f32) / (maxc as f32);
+        if let Some(max_channel) = profile.background_max_channel.filter(|m| maxc > *m) {
+            let rescale = (max_channel as f32) / (maxc as f32);
             r = (r as f32 * rescale).round() as u8;
             g = (g as f32 * rescale).round() as u8;
             b = (b as f32 * rescale).round() as u8;
@@ -0,0 +1,244 @@
+// Theme profiles: the lightness bands, chroma clamps and contrast target the processor
+// shapes the wallpaper's dominant hue into. `dark` reproduces the original hard-coded scheme.
+
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: &'static str,
+    /// Dark background with light text (true) or the other way round.
+    pub dark: bool,
+
+    // Background ("primary")
+    pub l_background: f32,
+    pub c_background_min: f32,
+    pub c_background_max: f32,
+    /// Dark profiles only: cap on relative luminance and on the largest sRGB channel.
+    pub background_luma_max: Option<f32>,
+    pub background_max_channel: Option<u8>,
+
+    // Accent ("secondary")
+    pub l_accent_target: f32,
+    pub l_accent_min: f32,
+    pub l_accent_max: f32,
+    pub c_accent_min: f32,
+    pub c_accent_max: f32,
+    pub min_contrast: f32,
+
+    // Neutrals
+    pub l_surface: f32,
+    pub l_foreground: f32,
+    pub c_neutral: f32,
+
+    // ANSI chromatic slots
+    pub ansi_l_normal: f32,
+    pub ansi_l_bright: f32,
+    pub ansi_c_min: f32,
+    pub ansi_c_max: f32,
+
+    /// How the accent hue is found in the wallpaper.
+    pub extraction: Extraction,
+}
+
+/// Accent extraction strategy. The histogram picks one dominant hue; the clustering modes group
+/// pixels in OkLab and can return several distinct accents for busy wallpapers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Extraction {
+    /// Center-weighted 72-bin hue histogram (the original algorithm).
+    Histogram,
+    /// k-means with fixed-seed k-means++ initialisation.
+    KMeans,
+    /// Median cut on the widest color box.
+    MedianCut,
+}
+
+pub const EXTRACTION_NAMES: [&str; 3] = ["histogram", "kmeans", "median-cut"];
+
+impl Extraction {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "histogram" => Ok(Extraction::Histogram),
+            "kmeans" | "k-means" => Ok(Extraction::KMeans),
+            "median-cut" | "mediancut" => Ok(Extraction::MedianCut),
+            other => Err(anyhow!("unknown extraction {} (known: {})", other, EXTRACTION_NAMES.join(", "))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Extraction::Histogram => "histogram",
+            Extraction::KMeans => "kmeans",
+            Extraction::MedianCut => "median-cut",
+        }
+    }
+}
+
+impl std::fmt::Display for Extraction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Which extraction each profile uses: `[extraction]` in the config (profile name -> method),
+/// with `--extraction` overriding it for every profile.
+#[derive(Debug, Clone, Default)]
+pub struct Extractions {
+    by_profile: BTreeMap<&'static str, Extraction>,
+    all: Option<Extraction>,
+}
+
+impl Extractions {
+    pub fn from_config(table: &BTreeMap<String, String>) -> Result<Self> {
+        let mut out = Self::default();
+        for (name, method) in table {
+            out.by_profile.insert(Profile::parse(name)?.name, Extraction::parse(method)?);
+        }
+        Ok(out)
+    }
+
+    /// Use `method` for every profile.
+    pub fn force(&mut self, method: Extraction) {
+        self.all = Some(method);
+    }
+
+    /// `profile` with the extraction picked for it; profiles not mentioned keep their own.
+    pub fn apply(&self, mut profile: Profile) -> Profile {
+        if let Some(method) = self.all.or_else(|| self.by_profile.get(profile.name).copied()) {
+            profile.extraction = method;
+        }
+        profile
+    }
+}
+
+pub const PROFILE_NAMES: [&str; 5] = ["dark", "light", "high-contrast", "vivid", "muted"];
+
+impl Profile {
+    pub fn named(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "vivid" => Some(Self::vivid()),
+            "muted" => Some(Self::muted()),
+            _ => None,
+        }
+    }
+
+    /// `named`, with an error listing the known profiles.
+    pub fn parse(name: &str) -> Result<Self> {
+        Self::named(name).ok_or_else(|| anyhow!("unknown profile {} (known: {})", name, PROFILE_NAMES.join(", ")))
+    }
+
+    /// Tinted near-black background with a pastel accent.
+    pub fn dark() -> Self {
+        Self {
+            name: "dark",
+            dark: true,
+            l_background: 0.20,
+            c_background_min: 0.030,
+            c_background_max: 0.110,
+            background_luma_max: Some(0.11),
+            background_max_channel: Some(0x22),
+            l_accent_target: 0.82,   // base lightness
+            l_accent_min: 0.78,      // never whiter than this band
+            l_accent_max: 0.86,
+            c_accent_min: 0.08,
+            c_accent_max: 0.18,
+            min_contrast: 3.0,
+            l_surface: 0.27,
+            l_foreground: 0.93,
+            c_neutral: 0.025,        // just enough tint to read as "warm"/"cool"
+            ansi_l_normal: 0.72,
+            ansi_l_bright: 0.82,
+            ansi_c_min: 0.09,
+            ansi_c_max: 0.16,
+            extraction: Extraction::Histogram,
+        }
+    }
+
+    /// Paper-like tinted background with a deep accent, for daytime use.
+    pub fn light() -> Self {
+        Self {
+            name: "light",
+            dark: false,
+            l_background: 0.97,
+            c_background_min: 0.008,
+            c_background_max: 0.030,
+            background_luma_max: None,
+            background_max_channel: None,
+            l_accent_target: 0.48,
+            l_accent_min: 0.40,
+            l_accent_max: 0.55,
+            c_accent_min: 0.10,
+            c_accent_max: 0.20,
+            min_contrast: 4.5,
+            l_surface: 0.91,
+            l_foreground: 0.25,
+            c_neutral: 0.020,
+            ansi_l_normal: 0.52,
+            ansi_l_bright: 0.45,
+            ansi_c_min: 0.10,
+            ansi_c_max: 0.18,
+            extraction: Extraction::Histogram,
+        }
+    }
+
+    /// Darker background, brighter accent and text, WCAG AAA contrast target.
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "high-contrast",
+            l_background: 0.14,
+            c_background_max: 0.060,
+            background_luma_max: Some(0.05),
+            background_max_channel: Some(0x14),
+            l_accent_target: 0.88,
+            l_accent_min: 0.85,
+            l_accent_max: 0.93,
+            c_accent_min: 0.06,
+            c_accent_max: 0.14,
+            min_contrast: 7.0,
+            l_surface: 0.24,
+            l_foreground: 0.98,
+            ansi_l_normal: 0.78,
+            ansi_l_bright: 0.88,
+            ..Self::dark()
+        }
+    }
+
+    /// Dark base with saturated, less pastel accents.
+    pub fn vivid() -> Self {
+        Self {
+            name: "vivid",
+            c_background_max: 0.140,
+            l_accent_target: 0.76,
+            l_accent_min: 0.70,
+            l_accent_max: 0.82,
+            c_accent_min: 0.14,
+            c_accent_max: 0.26,
+            c_neutral: 0.035,
+            ansi_c_min: 0.14,
+            ansi_c_max: 0.22,
+            ..Self::dark()
+        }
+    }
+
+    /// Dark base with greyed-out accents for a calm desktop.
+    pub fn muted() -> Self {
+        Self {
+            name: "muted",
+            c_background_min: 0.010,
+            c_background_max: 0.050,
+            c_accent_min: 0.03,
+            c_accent_max: 0.08,
+            c_neutral: 0.015,
+            ansi_c_min: 0.04,
+            ansi_c_max: 0.08,
+            ..Self::dark()
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self { Self::dark() }
+}
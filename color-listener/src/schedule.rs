@@ -0,0 +1,198 @@
+// Profile by time of day: one profile from sunrise to sunset and another at night (computed
+// locally from `latitude`/`longitude`), or a profile per fixed clock time. See `[schedule]` in
+// config.rs.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::ScheduleConfig;
+use crate::daemon::Daemon;
+use crate::profile::Profile;
+
+pub enum Schedule {
+    /// `day` between sunrise and sunset at the given position, `night` otherwise.
+    Solar { latitude: f64, longitude: f64, day: Profile, night: Profile },
+    /// Each profile from its time of day (minutes after midnight) until the next one; the last
+    /// one runs past midnight until the first.
+    Fixed(Vec<(u32, Profile)>),
+}
+
+/// Sunrise and sunset on one day; `AlwaysDay`/`AlwaysNight` beyond the polar circles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Daylight {
+    Between(DateTime<Local>, DateTime<Local>),
+    AlwaysDay,
+    AlwaysNight,
+}
+
+impl Schedule {
+    pub fn from_config(config: &ScheduleConfig) -> Result<Self> {
+        let position = config.latitude.zip(config.longitude);
+        if !config.times.is_empty() {
+            if position.is_some() || config.day.is_some() || config.night.is_some() {
+                return Err(anyhow!("[schedule] takes either times or latitude/longitude with day/night, not both"));
+            }
+            let mut times = Vec::new();
+            for (time, profile) in &config.times {
+                let at = NaiveTime::parse_from_str(time, "%H:%M")
+                    .map_err(|_| anyhow!("[schedule.times] expects HH:MM keys, got {}", time))?;
+                times.push((at.hour() * 60 + at.minute(), Profile::parse(profile)?));
+            }
+            times.sort_by_key(|(at, _)| *at);
+            return Ok(Schedule::Fixed(times));
+        }
+        let (latitude, longitude) = position
+            .ok_or_else(|| anyhow!("[schedule] needs latitude and longitude (or a [schedule.times] table)"))?;
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(anyhow!("[schedule] latitude must be within ±90 and longitude within ±180"));
+        }
+        let day = Profile::parse(config.day.as_deref().unwrap_or("light"))?;
+        let night = Profile::parse(config.night.as_deref().unwrap_or("dark"))?;
+        Ok(Schedule::Solar { latitude, longitude, day, night })
+    }
+
+    /// The profile the schedule asks for at `now`.
+    pub fn profile_at(&self, now: DateTime<Local>) -> &Profile {
+        match self {
+            Schedule::Solar { latitude, longitude, day, night } => {
+                let is_day = match daylight(now.date_naive(), *latitude, *longitude) {
+                    Daylight::Between(rise, set) => rise <= now && now < set,
+                    Daylight::AlwaysDay => true,
+                    Daylight::AlwaysNight => false,
+                };
+                if is_day { day } else { night }
+            }
+            Schedule::Fixed(times) => {
+                let minute = now.hour() * 60 + now.minute();
+                let current = times.iter().rev().find(|(at, _)| *at <= minute).or(times.last());
+                &current.expect("fixed schedules have at least one time").1
+            }
+        }
+    }
+
+    /// One line for the startup banner, e.g. `light from sunrise (06:12) to sunset (19:48), dark otherwise`.
+    pub fn describe(&self, now: DateTime<Local>) -> String {
+        match self {
+            Schedule::Solar { latitude, longitude, day, night } => {
+                let when = match daylight(now.date_naive(), *latitude, *longitude) {
+                    Daylight::Between(rise, set) => format!("from sunrise ({}) to sunset ({})", rise.format("%H:%M"), set.format("%H:%M")),
+                    Daylight::AlwaysDay => "all day today (midnight sun)".to_string(),
+                    Daylight::AlwaysNight => "not at all today (polar night)".to_string(),
+                };
+                format!("{} {}, {} otherwise (at {:.2}, {:.2})", day.name, when, night.name, latitude, longitude)
+            }
+            Schedule::Fixed(times) => times.iter()
+                .map(|(at, profile)| format!("{:02}:{:02} {}", at / 60, at % 60, profile.name))
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
+/// Sunrise and sunset on `date` at the given position (degrees, east and north positive), with the
+/// sunrise equation NOAA uses: good to a minute or two, which is plenty for switching themes.
+fn daylight(date: NaiveDate, latitude: f64, longitude: f64) -> Daylight {
+    let rad = PI / 180.0;
+    let j2000 = NaiveDate::from_ymd_opt(2000, 1, 1).expect("valid date");
+    let n = (date - j2000).num_days() as f64;
+    // Mean solar time, solar mean anomaly, equation of the center, ecliptic longitude.
+    let mean_time = n - longitude / 360.0;
+    let anomaly = (357.5291 + 0.98560028 * mean_time).rem_euclid(360.0);
+    let center = 1.9148 * (anomaly * rad).sin() + 0.0200 * (2.0 * anomaly * rad).sin() + 0.0003 * (3.0 * anomaly * rad).sin();
+    let ecliptic = (anomaly + center + 180.0 + 102.9372).rem_euclid(360.0);
+    let transit = 2451545.0 + mean_time + 0.0053 * (anomaly * rad).sin() - 0.0069 * (2.0 * ecliptic * rad).sin();
+    let declination = ((ecliptic * rad).sin() * (23.4397 * rad).sin()).asin();
+    // Hour angle at which the sun's upper edge touches the horizon, refraction included.
+    let cos_hour = ((-0.833 * rad).sin() - (latitude * rad).sin() * declination.sin()) / ((latitude * rad).cos() * declination.cos());
+    if cos_hour > 1.0 { return Daylight::AlwaysNight; }
+    if cos_hour < -1.0 { return Daylight::AlwaysDay; }
+    let hour = cos_hour.acos() / rad;
+    Daylight::Between(julian_to_local(transit - hour / 360.0), julian_to_local(transit + hour / 360.0))
+}
+
+fn julian_to_local(julian: f64) -> DateTime<Local> {
+    let millis = ((julian - 2440587.5) * 86_400_000.0).round() as i64;
+    Utc.timestamp_millis_opt(millis).single().unwrap_or_default().with_timezone(&Local)
+}
+
+/// Timers stand still while the machine is suspended, so instead of sleeping until the next
+/// boundary the schedule is looked at this often.
+const CHECK_EVERY: Duration = Duration::from_secs(30);
+
+impl Schedule {
+    /// Switch the daemon's profile whenever the schedule's pick changes, which re-derives the
+    /// theme from the current wallpaper. A profile set in between (`ctl profile`, `--profile`)
+    /// holds until the next boundary.
+    pub async fn run(self, daemon: Arc<Mutex<Daemon>>) {
+        let mut current = self.profile_at(Local::now()).name;
+        loop {
+            tokio::time::sleep(CHECK_EVERY).await;
+            let profile = self.profile_at(Local::now()).clone();
+            if profile.name == current { continue; }
+            current = profile.name;
+            println!("[schedule] switching to {}", profile.name);
+            let daemon = daemon.clone();
+            match tokio::task::spawn_blocking(move || daemon.lock().unwrap().set_profile(profile)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => eprintln!("[schedule] {e}"),
+                Err(e) => eprintln!("[schedule] {e}"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONDON: (f64, f64) = (51.5074, -0.1278);
+    const TROMSO: (f64, f64) = (69.6492, 18.9553);
+
+    fn utc_hm(t: DateTime<Local>) -> u32 {
+        let t = t.with_timezone(&Utc);
+        t.hour() * 60 + t.minute()
+    }
+
+    fn assert_near(what: &str, got: u32, want: (u32, u32)) {
+        let want = want.0 * 60 + want.1;
+        assert!(got.abs_diff(want) <= 3, "{what}: {:02}:{:02} UTC, expected {:02}:{:02}", got / 60, got % 60, want / 60, want % 60);
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn london_solstices() {
+        // Published times: 04:43/21:21 BST in June, 08:03/15:53 GMT in December.
+        let Daylight::Between(rise, set) = daylight(date(2024, 6, 20), LONDON.0, LONDON.1) else { panic!("no sunrise") };
+        assert_near("june sunrise", utc_hm(rise), (3, 43));
+        assert_near("june sunset", utc_hm(set), (20, 21));
+        let Daylight::Between(rise, set) = daylight(date(2024, 12, 21), LONDON.0, LONDON.1) else { panic!("no sunrise") };
+        assert_near("december sunrise", utc_hm(rise), (8, 3));
+        assert_near("december sunset", utc_hm(set), (15, 53));
+    }
+
+    #[test]
+    fn polar_night_and_midnight_sun() {
+        assert_eq!(daylight(date(2024, 12, 21), TROMSO.0, TROMSO.1), Daylight::AlwaysNight);
+        assert_eq!(daylight(date(2024, 6, 21), TROMSO.0, TROMSO.1), Daylight::AlwaysDay);
+        let schedule = Schedule::Solar { latitude: TROMSO.0, longitude: TROMSO.1, day: Profile::parse("light").unwrap(), night: Profile::parse("dark").unwrap() };
+        let noon = Local.with_ymd_and_hms(2024, 12, 21, 12, 0, 0).unwrap();
+        assert_eq!(schedule.profile_at(noon).name, "dark");
+    }
+
+    #[test]
+    fn fixed_last_profile_runs_past_midnight() {
+        let schedule = Schedule::Fixed(vec![(7 * 60, Profile::parse("light").unwrap()), (22 * 60, Profile::parse("dark").unwrap())]);
+        let at = |h, m| schedule.profile_at(Local.with_ymd_and_hms(2024, 1, 15, h, m, 0).unwrap()).name;
+        assert_eq!(at(0, 0), "dark");
+        assert_eq!(at(6, 59), "dark");
+        assert_eq!(at(7, 0), "light");
+        assert_eq!(at(21, 59), "light");
+        assert_eq!(at(23, 30), "dark");
+    }
+}
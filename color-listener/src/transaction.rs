@@ -0,0 +1,48 @@
+// File snapshots for all-or-nothing theme switches: capture every target before the
+// appliers run, put them back byte for byte if a required applier fails.
+
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+pub struct Snapshot {
+    /// Original contents per file; `None` for files that did not exist yet.
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl Snapshot {
+    pub fn capture<I: IntoIterator<Item = PathBuf>>(paths: I) -> Result<Self> {
+        let mut files: Vec<(PathBuf, Option<Vec<u8>>)> = Vec::new();
+        for path in paths {
+            if files.iter().any(|(p, _)| *p == path) { continue; }
+            let contents = match fs::read(&path) {
+                Ok(bytes) => Some(bytes),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(anyhow!("snapshot {}: {}", path.display(), e)),
+            };
+            files.push((path, contents));
+        }
+        Ok(Self { files })
+    }
+
+    /// Put every file back (removing ones that did not exist). Keeps going past failures
+    /// so one stuck file does not leave the others half-restored.
+    pub fn restore(&self) -> Result<()> {
+        let mut failed: Vec<String> = Vec::new();
+        for (path, contents) in &self.files {
+            let result = match contents {
+                Some(bytes) => {
+                    let tmp = path.with_extension("rollback.tmp");
+                    fs::write(&tmp, bytes).and_then(|_| fs::rename(&tmp, path))
+                        .with_context(|| format!("restore {}", path.display()))
+                }
+                None => match fs::remove_file(path) {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(anyhow!("remove {}: {}", path.display(), e)),
+                    _ => Ok(()),
+                },
+            };
+            if let Err(e) = result { failed.push(e.to_string()); }
+        }
+        if failed.is_empty() { Ok(()) } else { Err(anyhow!(failed.join("; "))) }
+    }
+}
@@ -0,0 +1,256 @@
+// Validation report for a palette: WCAG contrast of the text colors (foreground, accent, ANSI)
+// against the background, and how far apart the ANSI hues stay under simulated color-vision
+// deficiencies. Contrast decides whether a palette passes; the color-vision part is advisory,
+// since hues pulled towards one wallpaper cannot always stay apart for every viewer.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::color::{contrast_ratio_hex, hex_to_oklch, hex_to_rgb, linear_to_srgb, oklch_to_hex_gamut_ok, relative_luminance, srgb8_to_oklab, srgb_to_linear};
+use crate::palette::Palette;
+use crate::processor;
+use crate::profile::Profile;
+
+/// WCAG AA for body text; profiles can ask for more (`min_contrast`).
+const TEXT_MIN_CONTRAST: f32 = 4.5;
+
+/// Below this OkLab distance two colors read as the same one at a glance.
+const MIN_DISTANCE: f32 = 0.04;
+
+/// ANSI pairs that carry meaning when told apart: red/green, red/yellow and green/yellow are
+/// errors, warnings and success in compilers, diffs and test runners. The other hues sit at
+/// the same lightness by design and often collapse for dichromats without hurting much.
+const PAIRS: [(usize, usize); 3] = [(1, 2), (1, 3), (2, 3)];
+
+/// What the daemon does with a palette that fails validation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Policy {
+    /// Log the failures and apply it anyway.
+    Warn,
+    /// Keep the current theme.
+    Refuse,
+    /// Move failing colors away from the background until they pass, then apply.
+    Correct,
+}
+
+impl Policy {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "warn" => Ok(Policy::Warn),
+            "refuse" => Ok(Policy::Refuse),
+            "correct" => Ok(Policy::Correct),
+            other => Err(anyhow!("unknown validation policy {} (known: warn, refuse, correct)", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContrastCheck {
+    pub name: String,
+    pub color: String,
+    pub ratio: f32,
+    pub min: f32,
+    pub ok: bool,
+}
+
+/// The closest of the checked ANSI pairs for one kind of color vision.
+#[derive(Debug, Clone, Serialize)]
+pub struct VisionCheck {
+    pub vision: &'static str,
+    pub closest: (String, String),
+    pub distance: f32,
+    pub ok: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub passed: bool,
+    pub contrast: Vec<ContrastCheck>,
+    pub color_vision: Vec<VisionCheck>,
+}
+
+impl Report {
+    pub fn failures(&self) -> impl Iterator<Item = &ContrastCheck> {
+        self.contrast.iter().filter(|c| !c.ok)
+    }
+
+    /// One line for the log, e.g. `ok: fg 13.2, accent 8.1, ansi >= 4.0`.
+    pub fn summary(&self) -> String {
+        let ratio = |name: &str| self.contrast.iter().find(|c| c.name == name).map(|c| c.ratio).unwrap_or(0.0);
+        let ansi_min = self.contrast.iter().filter(|c| c.name.starts_with("color")).map(|c| c.ratio).fold(f32::INFINITY, f32::min);
+        let mut out = format!("{}: fg {:.1}, accent {:.1}, ansi >= {:.1}",
+            if self.passed { "ok" } else { "FAILED" }, ratio("foreground"), ratio("accent"), ansi_min);
+        let failures: Vec<String> = self.failures().map(|c| format!("{} {:.2} < {:.1}", c.name, c.ratio, c.min)).collect();
+        if !failures.is_empty() { out.push_str(&format!(" ({})", failures.join(", "))); }
+        let confusable: Vec<String> = self.color_vision.iter().filter(|v| !v.ok)
+            .map(|v| format!("{} {}/{}", v.vision, v.closest.0, v.closest.1))
+            .collect();
+        if !confusable.is_empty() { out.push_str(&format!("; hard to tell apart for {}", confusable.join(", "))); }
+        out
+    }
+
+    /// Table for `extract --report`.
+    pub fn render(&self) -> String {
+        let mut out = format!("{:<14}{:<10}{:>7}{:>6}\n", "contrast", "color", "ratio", "min");
+        for c in &self.contrast {
+            out.push_str(&format!("{:<14}{:<10}{:>7.2}{:>6.1}  {}\n", c.name, c.color, c.ratio, c.min, if c.ok { "ok" } else { "FAIL" }));
+        }
+        out.push_str(&format!("\n{:<14}{:<17}{:>9}\n", "color vision", "closest pair", "distance"));
+        for v in &self.color_vision {
+            let pair = format!("{}/{}", v.closest.0, v.closest.1);
+            out.push_str(&format!("{:<14}{:<17}{:>9.3}  {}\n", v.vision, pair, v.distance, if v.ok { "ok" } else { "low" }));
+        }
+        let failed = self.failures().count();
+        out.push_str(&if failed == 0 { "\npassed\n".to_string() } else { format!("\nfailed: {} contrast check(s)\n", failed) });
+        out
+    }
+}
+
+/// Check `palette` against the contrast targets of `profile`.
+pub fn validate(palette: &Palette, profile: &Profile) -> Report {
+    let contrast: Vec<ContrastCheck> = targets(palette, profile).into_iter()
+        .map(|(name, color, min)| {
+            let ratio = contrast_ratio_hex(&color, &palette.background);
+            ContrastCheck { name, color, ratio, min, ok: ratio >= min }
+        })
+        .collect();
+    let color_vision = VISIONS.iter().map(|(vision, matrix)| closest_pair(palette, vision, matrix)).collect();
+    Report { passed: contrast.iter().all(|c| c.ok), contrast, color_vision }
+}
+
+/// `palette` with every color that misses its contrast target moved in lightness (hue and
+/// chroma kept) away from the background until it meets it. Accent variants follow the accent.
+pub fn correct(palette: &Palette, profile: &Profile) -> Palette {
+    let mut out = palette.clone();
+    let dark = is_dark(palette);
+    for check in validate(palette, profile).failures() {
+        let fixed = lift(&check.color, &palette.background, check.min, dark);
+        match check.name.as_str() {
+            "foreground" => out.foreground = fixed,
+            "accent" => {
+                out.accent_variants = processor::accent_variants(&fixed, profile);
+                out.accent = fixed;
+            }
+            name => if let Some(i) = name.strip_prefix("color").and_then(|i| i.parse::<usize>().ok()) {
+                out.ansi[i] = fixed;
+            } else if let Some(i) = name.strip_prefix("accent-").and_then(|i| i.parse::<usize>().ok()) {
+                out.accents[i - 2] = fixed;
+            },
+        }
+    }
+    out
+}
+
+// (name, color, minimum ratio) for every color drawn on the background. The ANSI slot that is
+// the background itself and the gray right next to it (color8 on dark, color7 on light) are
+// meant to be faint and are left out.
+fn targets(palette: &Palette, profile: &Profile) -> Vec<(String, String, f32)> {
+    let ansi_min = profile.min_contrast.min(TEXT_MIN_CONTRAST);
+    let skip = if is_dark(palette) { [0, 8] } else { [15, 7] };
+    let mut out = vec![
+        ("foreground".to_string(), palette.foreground.clone(), profile.min_contrast.max(TEXT_MIN_CONTRAST)),
+        ("accent".to_string(), palette.accent.clone(), profile.min_contrast),
+    ];
+    for (i, color) in palette.accents.iter().enumerate() {
+        out.push((format!("accent-{}", i + 2), color.clone(), profile.min_contrast));
+    }
+    for (i, color) in palette.ansi.iter().enumerate() {
+        if skip.contains(&i) || *color == palette.background { continue; }
+        out.push((format!("color{i}"), color.clone(), ansi_min));
+    }
+    out
+}
+
+fn is_dark(palette: &Palette) -> bool {
+    let luminance = |hex: &str| relative_luminance(hex_to_rgb(hex).unwrap_or((127, 127, 127)));
+    luminance(&palette.background) < luminance(&palette.foreground)
+}
+
+fn lift(color: &str, background: &str, min: f32, dark: bool) -> String {
+    let (mut l, c, h) = hex_to_oklch(color);
+    let step = if dark { 0.01 } else { -0.01 };
+    let mut out = color.to_string();
+    while contrast_ratio_hex(&out, background) < min && (0.0..=1.0).contains(&(l + step)) {
+        l += step;
+        out = oklch_to_hex_gamut_ok(l, c, h);
+    }
+    out
+}
+
+type Matrix = [[f32; 3]; 3];
+
+/// Full-severity dichromacy simulation in linear RGB (Machado, Oliveira & Fernandes 2009).
+const VISIONS: [(&str, Matrix); 3] = [
+    ("protanopia", [[0.152286, 1.052583, -0.204868], [0.114503, 0.786281, 0.099216], [-0.003882, -0.048116, 1.051998]]),
+    ("deuteranopia", [[0.367322, 0.860646, -0.227968], [0.280085, 0.672501, 0.047413], [-0.011820, 0.042940, 0.968881]]),
+    ("tritanopia", [[1.255528, -0.076749, -0.178779], [-0.078411, 0.930809, 0.147602], [0.004733, 0.691367, 0.303900]]),
+];
+
+fn closest_pair(palette: &Palette, vision: &'static str, matrix: &Matrix) -> VisionCheck {
+    let (distance, i, j) = PAIRS.iter()
+        .map(|&(i, j)| {
+            let (a, b) = (simulate(&palette.ansi[i], matrix), simulate(&palette.ansi[j], matrix));
+            (((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt(), i, j)
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .expect("PAIRS is not empty");
+    VisionCheck { vision, closest: (format!("color{i}"), format!("color{j}")), distance, ok: distance >= MIN_DISTANCE }
+}
+
+// OkLab of `hex` as seen with the given deficiency.
+fn simulate(hex: &str, m: &Matrix) -> (f32, f32, f32) {
+    let (r, g, b) = hex_to_rgb(hex).unwrap_or((127, 127, 127));
+    let lin = [r, g, b].map(|c| srgb_to_linear(c as f32 / 255.0));
+    let to8 = |row: &[f32; 3]| {
+        let v = (row[0] * lin[0] + row[1] * lin[1] + row[2] * lin[2]).clamp(0.0, 1.0);
+        (linear_to_srgb(v) * 255.0).round() as u8
+    };
+    srgb8_to_oklab(to8(&m[0]), to8(&m[1]), to8(&m[2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Palette {
+        processor::palette_from_colors("#1A1B26", "#7AA2F7", &Profile::dark())
+    }
+
+    #[test]
+    fn corrected_palette_passes() {
+        let mut palette = base();
+        palette.foreground = "#2A2B36".to_string();
+        palette.accent = "#2B3450".to_string();
+        palette.accents = vec!["#3A2020".to_string(), "#203A20".to_string()];
+        palette.ansi[1] = "#3A1010".to_string();
+        palette.ansi[12] = "#202840".to_string();
+        let profile = Profile::dark();
+        assert!(!validate(&palette, &profile).passed);
+
+        let corrected = correct(&palette, &profile);
+        let report = validate(&corrected, &profile);
+        let failures: Vec<&String> = report.failures().map(|c| &c.name).collect();
+        assert!(report.passed, "still failing: {failures:?}");
+        assert_eq!(corrected.background, palette.background);
+    }
+
+    #[test]
+    fn accent_n_corrects_accents_n_minus_2() {
+        let profile = Profile::dark();
+        let readable = base().accent;
+        for failing in 0..3 {
+            let mut palette = base();
+            palette.accents = vec![readable.clone(); 3];
+            palette.accents[failing] = "#2A2030".to_string();
+            let corrected = correct(&palette, &profile);
+            for (i, color) in corrected.accents.iter().enumerate() {
+                if i == failing {
+                    assert_ne!(*color, palette.accents[i], "accent-{} not corrected", i + 2);
+                } else {
+                    assert_eq!(*color, readable, "accent-{} changed for a failing accent-{}", i + 2, failing + 2);
+                }
+            }
+            assert!(validate(&corrected, &profile).passed);
+        }
+    }
+}
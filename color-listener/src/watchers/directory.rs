@@ -0,0 +1,26 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use watchexec::WatchedPath;
+
+use super::{is_image_path, newest_file, watch_paths, Target, WallpaperEvent};
+
+/// Treats the most recently modified image in a directory as the current wallpaper.
+/// Useful with tools that copy/download the active wallpaper into a fixed folder.
+pub struct DirectoryWatcher {
+    dir: PathBuf,
+}
+
+impl DirectoryWatcher {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub async fn start(self, debounce: Duration) -> Result<mpsc::Receiver<WallpaperEvent>> {
+        let dir = self.dir.clone();
+        let resolve = Arc::new(move || Ok(newest_file(&dir, false, &is_image_path)?));
+        watch_paths("directory", Target::Paths(vec![WatchedPath::non_recursive(self.dir)]), debounce, resolve).await
+    }
+}
@@ -1,110 +1,232 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use image::imageops::FilterType;
+use image::RgbImage;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::sync::{Arc, Mutex};
-use watchexec::Watchexec; 
-// Tag enum path changed in watchexec v8: rather than importing, we inspect event debug.
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::UnixStream;
+use std::time::Duration;
 use tokio::sync::mpsc;
-use image::DynamicImage;
-use tokio::task;
 
-/// Message sent to main when a new wallpaper path is detected.
-/// Contains the absolute resolved path and the decoded image.
-pub struct WallpaperEvent { pub path: PathBuf, pub image: DynamicImage }
+use super::{watch_paths_with_trigger, Target, WallpaperEvent};
+use crate::cache::{fnv1a64, PaletteCache};
+use crate::hypr_ipc;
+use crate::paths::expand_home;
 
-/// Watches a hyprpaper config file for wallpaper changes, parses the last
-/// `wallpaper =` line (ignoring commented lines), and notifies via channel.
+/// Which monitor's wallpaper drives the theme.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MonitorChoice {
+    /// The monitor with focus, followed live through Hyprland's event socket.
+    Focused,
+    /// One monitor by name, e.g. `eDP-1`.
+    Named(String),
+    /// All monitors: their wallpapers side by side feed a single palette.
+    Blend,
+}
+
+impl MonitorChoice {
+    pub fn parse(value: &str) -> Self {
+        match value.trim() {
+            "focused" | "" => MonitorChoice::Focused,
+            "blend" | "all" => MonitorChoice::Blend,
+            name => MonitorChoice::Named(name.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for MonitorChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MonitorChoice::Focused => write!(f, "focused"),
+            MonitorChoice::Named(name) => write!(f, "{}", name),
+            MonitorChoice::Blend => write!(f, "blend"),
+        }
+    }
+}
+
+/// Watches a hyprpaper config file for wallpaper changes, keeps the wallpaper of every
+/// monitor (`wallpaper = monitor, /path`; an empty monitor covers the rest) and notifies
+/// via channel whenever the one picked by `MonitorChoice` changes.
 pub struct HyprpaperWatcher {
     conf_path: PathBuf,
+    monitor: MonitorChoice,
+}
+
+/// Parsed `wallpaper =` lines: the last one per monitor (`""` for the catch-all) and the last
+/// one overall, which is what the watcher used before it knew about monitors.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Wallpapers {
+    by_monitor: BTreeMap<String, PathBuf>,
+    last: Option<PathBuf>,
 }
 
 impl HyprpaperWatcher {
     pub fn new<P: Into<PathBuf>>(hyprpaper_conf_path: P) -> Self {
-        Self { conf_path: hyprpaper_conf_path.into() }
+        Self { conf_path: hyprpaper_conf_path.into(), monitor: MonitorChoice::Focused }
     }
 
-    /// Starts watching asynchronously. Returns a receiver yielding
-    /// `WallpaperEvent::Updated` events each time the last wallpaper changes.
-    pub async fn start(self) -> Result<mpsc::Receiver<WallpaperEvent>> {
-        let (tx, rx) = mpsc::channel(4);
-        let conf_path = self.conf_path.clone();
-        let last_sent: Arc<Mutex<Option<PathBuf>>> = Arc::new(Mutex::new(None));
-
-        // Initial emit
-        if let Some(wp) = parse_last_wallpaper_line(&conf_path)? { maybe_send_async(tx.clone(), last_sent.clone(), wp).await; }
-
-        let handler_tx = tx.clone();
-        let handler_conf_path = conf_path.clone();
-        let handler_last = last_sent.clone();
+    pub fn with_monitor(mut self, monitor: MonitorChoice) -> Self {
+        self.monitor = monitor;
+        self
+    }
 
-        let wx = Watchexec::new(move | action| {
-            let mut changed = false;
-            for event in action.events.iter() {
-                let dbg = format!("{event:?}");
-                if dbg.contains("Modify") || dbg.contains("Create") { changed = true; break; }
-            }
-            if changed {
-                let tx2 = handler_tx.clone();
-                let conf2 = handler_conf_path.clone();
-                let last2 = handler_last.clone();
-                tokio::spawn(async move {
-                    match parse_last_wallpaper_line(&conf2) {
-                        Ok(Some(p)) => maybe_send_async(tx2, last2, p).await,
-                        Ok(None) => {},
-                        Err(e) => eprintln!("[hyprpaper watcher] parse error: {e}"),
-                    }
-                });
+    /// Starts watching asynchronously. Returns a receiver yielding
+    /// `WallpaperEvent::Updated` events each time the chosen wallpaper changes.
+    pub async fn start(self, debounce: Duration) -> Result<mpsc::Receiver<WallpaperEvent>> {
+        let focused: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+        let mut trigger = None;
+        if self.monitor == MonitorChoice::Focused && hypr_ipc::running() {
+            match hypr_ipc::focused_monitor() {
+                Ok(name) => *focused.lock().unwrap() = name,
+                Err(e) => eprintln!("[hyprpaper watcher] focused monitor unknown: {e}"),
             }
-            action
-        })?;
-
-        wx.config.pathset([conf_path]);
+            let (tx, rx) = mpsc::unbounded_channel();
+            tokio::spawn(follow_focus(focused.clone(), tx));
+            trigger = Some(rx);
+        }
 
-        tokio::spawn(async move {
-            if let Err(e) = wx.main().await { eprintln!("[hyprpaper watcher] engine error: {e}"); }
+        let conf_path = self.conf_path.clone();
+        let monitor = self.monitor.clone();
+        let previous: Mutex<Wallpapers> = Mutex::new(Wallpapers::default());
+        let resolve = Arc::new(move || {
+            let wallpapers = parse_wallpapers(&conf_path)?;
+            log_changes(&previous.lock().unwrap(), &wallpapers);
+            *previous.lock().unwrap() = wallpapers.clone();
+            let focused = focused.lock().unwrap().clone();
+            pick(&wallpapers, &monitor, focused.as_deref())
         });
-
-        Ok(rx)
-    }
-}
-async fn maybe_send_async(tx: mpsc::Sender<WallpaperEvent>, last_sent: Arc<Mutex<Option<PathBuf>>>, path: PathBuf) {
-    if { let last = last_sent.lock().unwrap(); last.as_ref() == Some(&path) } { return; }
-    match task::spawn_blocking({ let path = path.clone(); move || image::open(&path).map(|img| (path, img)) }).await {
-        Ok(Ok((p,img))) => {
-            { let mut last = last_sent.lock().unwrap(); *last = Some(p.clone()); }
-            if tx.send(WallpaperEvent { path: p, image: img }).await.is_err() { eprintln!("[hyprpaper watcher] receiver dropped"); }
-        }
-        Ok(Err(e)) => eprintln!("[hyprpaper watcher] failed to load image: {e}"),
-        Err(e) => eprintln!("[hyprpaper watcher] join error: {e}"),
+        watch_paths_with_trigger("hyprpaper", Target::File(self.conf_path), debounce, resolve, trigger).await
     }
 }
 
-/// Extract the last non-comment `wallpaper =` line, returning the resolved path.
-fn parse_last_wallpaper_line(conf_path: &Path) -> Result<Option<PathBuf>> {
+/// Parse every non-comment `wallpaper =` line.
+fn parse_wallpapers(conf_path: &Path) -> Result<Wallpapers> {
     let content = match fs::read_to_string(conf_path) { Ok(c)=>c, Err(e) => {
-        if e.kind() == std::io::ErrorKind::NotFound { return Ok(None); } else { return Err(e.into()); }
+        if e.kind() == std::io::ErrorKind::NotFound { return Ok(Wallpapers::default()); } else { return Err(e.into()); }
     }};
-    let mut last: Option<String> = None;
+    let mut out = Wallpapers::default();
     for line in content.lines() {
         let trimmed = line.trim();
         if trimmed.is_empty() || trimmed.starts_with('#') { continue; }
         // hyprpaper line samples: `wallpaper = , /path/to/img.jpg` or `wallpaper = monitor, /path`.
-        if let Some(rest) = trimmed.strip_prefix("wallpaper") {
-            // Expect format: `= ...` somewhere
-            if let Some(eq_idx) = rest.find('=') {
-                let after_eq = &rest[eq_idx+1..].trim();
-                // Split on comma, take last segment as path (hyprpaper allows leading monitor spec)
-                let path_part = after_eq.split(',').last().map(|s| s.trim());
-                if let Some(p) = path_part { if !p.is_empty() { last = Some(p.to_string()); } }
+        let Some(rest) = trimmed.strip_prefix("wallpaper") else { continue };
+        let Some(value) = rest.trim_start().strip_prefix('=') else { continue };
+        // Monitor before the first comma (may be empty), path after it.
+        let (monitor, path) = value.split_once(',').unwrap_or(("", value));
+        let path = path.trim();
+        if path.is_empty() { continue; }
+        let path = expand_home(path);
+        out.by_monitor.insert(monitor.trim().to_string(), path.clone());
+        out.last = Some(path);
+    }
+    Ok(out)
+}
+
+// A monitor without its own line shows the catch-all one.
+fn for_monitor<'a>(wallpapers: &'a Wallpapers, name: &str) -> Option<&'a PathBuf> {
+    wallpapers.by_monitor.get(name).or_else(|| wallpapers.by_monitor.get(""))
+}
+
+fn pick(wallpapers: &Wallpapers, monitor: &MonitorChoice, focused: Option<&str>) -> Result<Option<PathBuf>> {
+    match monitor {
+        // Outside Hyprland (or before the first focus event) fall back to the last line.
+        MonitorChoice::Focused => Ok(focused.and_then(|f| for_monitor(wallpapers, f)).or(wallpapers.last.as_ref()).cloned()),
+        MonitorChoice::Named(name) => {
+            let path = for_monitor(wallpapers, name).cloned();
+            if path.is_none() { eprintln!("[hyprpaper watcher] no wallpaper line for monitor {}", name); }
+            Ok(path)
+        }
+        MonitorChoice::Blend => {
+            let mut paths: Vec<&PathBuf> = wallpapers.by_monitor.values().collect();
+            paths.sort();
+            paths.dedup();
+            match paths.as_slice() {
+                [] => Ok(None),
+                [only] => Ok(Some((*only).clone())),
+                many => blend(many).map(Some),
             }
         }
     }
-    if let Some(p) = last { return Ok(Some(resolve_path(&p))); }
-    Ok(None)
 }
 
-fn resolve_path(s: &str) -> PathBuf {
-    if let Some(rest) = s.strip_prefix("$HOME/") { if let Some(home) = std::env::var_os("HOME") { return PathBuf::from(home).join(rest); }}
-    if let Some(rest) = s.strip_prefix("~/") { if let Some(home) = std::env::var_os("HOME") { return PathBuf::from(home).join(rest); }}
-    PathBuf::from(s)
-}
\ No newline at end of file
+fn log_changes(before: &Wallpapers, after: &Wallpapers) {
+    for (monitor, path) in &after.by_monitor {
+        if before.by_monitor.get(monitor) != Some(path) {
+            let label = if monitor.is_empty() { "*" } else { monitor.as_str() };
+            println!("[hyprpaper] {}: {}", label, path.display());
+        }
+    }
+}
+
+/// Blends kept on disk; older ones are deleted whenever a new one is written.
+const KEEP_BLENDS: usize = 4;
+
+// Side-by-side composite of every wallpaper, each scaled to the same size so every monitor
+// weighs the same in the palette. Stored under the cache dir by a hash of the inputs (paths and
+// mtimes), so the cache, history and `lock` see a distinct file per combination.
+fn blend(paths: &[&PathBuf]) -> Result<PathBuf> {
+    const W: u32 = 480;
+    const H: u32 = 270;
+    let mut key = String::new();
+    for p in paths {
+        let mtime = fs::metadata(p).and_then(|m| m.modified()).ok();
+        key.push_str(&format!("{}:{:?};", p.display(), mtime));
+    }
+    let dir = PaletteCache::new(PaletteCache::default_dir()).blends_dir();
+    let out = dir.join(format!("{:016x}.png", fnv1a64(key.as_bytes())));
+    if out.exists() { return Ok(out); }
+
+    let mut canvas = RgbImage::new(W * paths.len() as u32, H);
+    for (i, p) in paths.iter().enumerate() {
+        let img = image::open(p).with_context(|| format!("open {}", p.display()))?;
+        let tile = img.resize_to_fill(W, H, FilterType::Triangle).to_rgb8();
+        image::imageops::replace(&mut canvas, &tile, (i as u32 * W) as i64, 0);
+    }
+    fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+    canvas.save(&out).map_err(|e| anyhow!("save {}: {}", out.display(), e))?;
+    if let Err(e) = prune_blends(&dir) { eprintln!("[hyprpaper watcher] pruning old blends: {e}"); }
+    Ok(out)
+}
+
+// Drop all but the `KEEP_BLENDS` most recently written blends.
+fn prune_blends(dir: &Path) -> std::io::Result<()> {
+    let mut blends: Vec<(std::time::SystemTime, PathBuf)> = fs::read_dir(dir)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "png"))
+        .filter_map(|p| Some((fs::metadata(&p).and_then(|m| m.modified()).ok()?, p)))
+        .collect();
+    blends.sort_by_key(|b| std::cmp::Reverse(b.0));
+    for (_, old) in blends.into_iter().skip(KEEP_BLENDS) {
+        fs::remove_file(&old)?;
+    }
+    Ok(())
+}
+
+// Track `focusedmon>>NAME,WORKSPACE` events and poke the watcher when focus moves to
+// another monitor.
+async fn follow_focus(focused: Arc<Mutex<Option<String>>>, trigger: mpsc::UnboundedSender<()>) {
+    let stream = match hypr_ipc::event_socket() {
+        Ok(path) => UnixStream::connect(&path).await,
+        Err(e) => { eprintln!("[hyprpaper watcher] {e}"); return; }
+    };
+    let stream = match stream {
+        Ok(s) => s,
+        Err(e) => { eprintln!("[hyprpaper watcher] event socket: {e}"); return; }
+    };
+    let mut lines = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let Some(data) = line.strip_prefix("focusedmon>>") else { continue };
+        let name = data.split(',').next().unwrap_or(data).to_string();
+        let changed = {
+            let mut current = focused.lock().unwrap();
+            let changed = current.as_deref() != Some(name.as_str());
+            *current = Some(name);
+            changed
+        };
+        if changed && trigger.send(()).is_err() { return; }
+    }
+    eprintln!("[hyprpaper watcher] event socket closed; focus no longer followed");
+}
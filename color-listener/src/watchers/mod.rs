@@ -1,3 +1,271 @@
 pub mod hyprpaper;
+pub mod symlink;
+pub mod swww;
+pub mod wpaperd;
+pub mod directory;
 
-pub use hyprpaper::HyprpaperWatcher;
+pub use hyprpaper::{HyprpaperWatcher, MonitorChoice};
+pub use symlink::SymlinkWatcher;
+pub use swww::SwwwWatcher;
+pub use wpaperd::WpaperdWatcher;
+pub use directory::DirectoryWatcher;
+
+use anyhow::Result;
+use image::DynamicImage;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::mpsc;
+use tokio::task;
+use watchexec::{WatchedPath, Watchexec};
+use watchexec_events::filekind::{AccessKind, AccessMode, FileEventKind, ModifyKind};
+use watchexec_events::{Event, Tag};
+
+/// Message sent to main when a new wallpaper path is detected.
+/// Contains the absolute resolved path and the decoded image.
+pub struct WallpaperEvent { pub path: PathBuf, pub image: DynamicImage }
+
+/// Looks up the current wallpaper for a watcher; `Ok(None)` means "nothing set yet".
+pub(crate) type Resolver = Arc<dyn Fn() -> Result<Option<PathBuf>> + Send + Sync>;
+
+/// How long a watcher waits for things to settle before looking at them, unless configured.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// A steady stream of changes (a slideshow rewriting its file every few ms) still gets
+/// resolved at least this often.
+const MAX_SETTLE: Duration = Duration::from_secs(2);
+
+/// What a watcher listens to.
+pub(crate) enum Target {
+    /// Any change under these paths.
+    Paths(Vec<WatchedPath>),
+    /// One file. Its directory is watched rather than the file itself, so atomic-rename saves
+    /// and delete/recreate are seen; if it is a symlink (e.g. into a dotfiles repo), every file
+    /// along the link is followed as well, and the watch moves when the link is repointed.
+    File(PathBuf),
+}
+
+/// Shared engine behind every watcher: emit the current wallpaper once, then re-run
+/// `resolve` whenever `target` changes and send a `WallpaperEvent`
+/// if the resolved wallpaper differs from the last one sent. Changes are debounced:
+/// `resolve` runs once `debounce` has passed without another one, so a file written in
+/// several steps is only looked at (and its image decoded) once, in its final state.
+pub(crate) async fn watch_paths(tag: &'static str, target: Target, debounce: Duration, resolve: Resolver) -> Result<mpsc::Receiver<WallpaperEvent>> {
+    watch_paths_with_trigger(tag, target, debounce, resolve, None).await
+}
+
+/// `watch_paths`, plus a re-resolve whenever `trigger` fires, for inputs that are not files
+/// (e.g. which monitor has focus).
+pub(crate) async fn watch_paths_with_trigger(
+    tag: &'static str,
+    target: Target,
+    debounce: Duration,
+    resolve: Resolver,
+    trigger: Option<mpsc::UnboundedReceiver<()>>,
+) -> Result<mpsc::Receiver<WallpaperEvent>> {
+    let (tx, rx) = mpsc::channel(4);
+    let last_sent: Arc<Mutex<Option<PathBuf>>> = Arc::new(Mutex::new(None));
+
+    // Initial emit
+    if let Some(wp) = resolve_blocking(&resolve).await? { maybe_send_async(tag, tx.clone(), last_sent.clone(), wp).await; }
+
+    // File changes and triggers only poke a single task, which waits for them to stop and
+    // then resolves once; pokes arriving meanwhile are folded into that one run.
+    let (poke, mut pokes) = mpsc::unbounded_channel::<()>();
+    if let Some(mut trigger) = trigger {
+        let poke = poke.clone();
+        tokio::spawn(async move {
+            while trigger.recv().await.is_some() {
+                if poke.send(()).is_err() { return; }
+            }
+        });
+    }
+
+    // For a file target, the files whose events count; empty means every event under the paths.
+    let followed: Arc<Mutex<Vec<FileKey>>> = Arc::new(Mutex::new(Vec::new()));
+    let paths = match &target {
+        Target::Paths(paths) => paths.clone(),
+        Target::File(file) => {
+            let chain = link_chain(file);
+            *followed.lock().unwrap() = chain.iter().filter_map(|p| file_key(p)).collect();
+            chain_dirs(&chain)
+        }
+    };
+
+    let handler_followed = followed.clone();
+    let wx = Watchexec::new(move |action| {
+        let changed = {
+            let followed = handler_followed.lock().unwrap();
+            action.events.iter().any(|event| is_change(event) && concerns(event, &followed))
+        };
+        if changed { let _ = poke.send(()); }
+        action
+    })?;
+
+    wx.config.pathset(paths);
+
+    let config = wx.config.clone();
+    tokio::spawn(async move {
+        while pokes.recv().await.is_some() {
+            if !settle(&mut pokes, debounce).await { return; }
+            // A repointed link moves the watch to the new target's directory.
+            if let Target::File(file) = &target {
+                let chain = link_chain(file);
+                let keys: Vec<FileKey> = chain.iter().filter_map(|p| file_key(p)).collect();
+                let moved = *followed.lock().unwrap() != keys;
+                if moved {
+                    println!("[{tag} watcher] following {}", chain.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(" -> "));
+                    *followed.lock().unwrap() = keys;
+                    config.pathset(chain_dirs(&chain));
+                }
+            }
+            match resolve_blocking(&resolve).await {
+                Ok(Some(p)) => maybe_send_async(tag, tx.clone(), last_sent.clone(), p).await,
+                Ok(None) => {},
+                Err(e) => eprintln!("[{tag} watcher] resolve error: {e}"),
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        if let Err(e) = wx.main().await { eprintln!("[{tag} watcher] engine error: {e}"); }
+    });
+
+    Ok(rx)
+}
+
+// Content, name and existence changes. Metadata-only changes (atime, permissions) and reads
+// are noise; a writer closing the file is the most reliable sign a save is complete.
+fn is_change(event: &Event) -> bool {
+    event.tags.iter().any(|tag| match tag {
+        Tag::FileEventKind(kind) => match kind {
+            FileEventKind::Create(_) | FileEventKind::Remove(_) | FileEventKind::Any => true,
+            FileEventKind::Modify(ModifyKind::Metadata(_)) => false,
+            FileEventKind::Modify(_) => true,
+            FileEventKind::Access(AccessKind::Close(AccessMode::Write)) => true,
+            FileEventKind::Access(_) | FileEventKind::Other => false,
+        },
+        _ => false,
+    })
+}
+
+// Whether the event is about a followed file. A rename reports both names, so a save that
+// renames `conf.tmp` over `conf` matches through its destination. Events without paths
+// (e.g. after the backend dropped events) count, better one extra look than a missed change.
+fn concerns(event: &Event, followed: &[FileKey]) -> bool {
+    if followed.is_empty() { return true; }
+    let mut paths = event.paths().peekable();
+    if paths.peek().is_none() { return true; }
+    paths.any(|(path, _)| file_key(path).is_some_and(|key| followed.contains(&key)))
+}
+
+/// A file as (canonical directory, name): the file itself may be gone or replaced, but its
+/// directory is stable, and events may name it through a different (symlinked) directory.
+type FileKey = (PathBuf, OsString);
+
+fn file_key(path: &Path) -> Option<FileKey> {
+    let dir = path.parent()?;
+    let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    Some((dir, path.file_name()?.to_os_string()))
+}
+
+/// Links are followed for at most this many hops, which also ends symlink loops.
+const MAX_LINK_HOPS: usize = 8;
+
+/// `file` and every file its symlinks lead to, e.g. `~/.config/hypr/hyprpaper.conf` and the
+/// file in the dotfiles repo it points at.
+fn link_chain(file: &Path) -> Vec<PathBuf> {
+    let mut chain = vec![file.to_path_buf()];
+    while chain.len() <= MAX_LINK_HOPS {
+        let current = chain.last().unwrap();
+        let Ok(target) = std::fs::read_link(current) else { break };
+        let target = current.parent().map(|dir| dir.join(&target)).unwrap_or(target);
+        if chain.contains(&target) { break; }
+        chain.push(target);
+    }
+    chain
+}
+
+// Directories holding the chain's files; ones that do not exist (a dangling link) are skipped.
+fn chain_dirs(chain: &[PathBuf]) -> Vec<WatchedPath> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for file in chain {
+        let Some(dir) = file.parent() else { continue };
+        let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        if dir.is_dir() && !dirs.contains(&dir) { dirs.push(dir); }
+    }
+    dirs.into_iter().map(WatchedPath::non_recursive).collect()
+}
+
+// Swallow pokes until `debounce` passes without one (or `MAX_SETTLE` since the first).
+// False once every sender is gone.
+async fn settle(pokes: &mut mpsc::UnboundedReceiver<()>, debounce: Duration) -> bool {
+    let deadline = Instant::now() + MAX_SETTLE.max(debounce);
+    loop {
+        let wait = debounce.min(deadline.saturating_duration_since(Instant::now()));
+        if wait.is_zero() {
+            while pokes.try_recv().is_ok() {}
+            return true;
+        }
+        match tokio::time::timeout(wait, pokes.recv()).await {
+            Ok(Some(())) => continue,
+            Ok(None) => return false,
+            Err(_) => return true,
+        }
+    }
+}
+
+// Resolvers read files and may compose images (hyprpaper's blend), so they run on the blocking
+// pool rather than on an async worker.
+async fn resolve_blocking(resolve: &Resolver) -> Result<Option<PathBuf>> {
+    let resolve = resolve.clone();
+    task::spawn_blocking(move || resolve()).await?
+}
+
+async fn maybe_send_async(tag: &'static str, tx: mpsc::Sender<WallpaperEvent>, last_sent: Arc<Mutex<Option<PathBuf>>>, path: PathBuf) {
+    let already_sent = last_sent.lock().unwrap().as_ref() == Some(&path);
+    if already_sent { return; }
+    match task::spawn_blocking({ let path = path.clone(); move || image::open(&path).map(|img| (path, img)) }).await {
+        Ok(Ok((p,img))) => {
+            { let mut last = last_sent.lock().unwrap(); *last = Some(p.clone()); }
+            if tx.send(WallpaperEvent { path: p, image: img }).await.is_err() { eprintln!("[{tag} watcher] receiver dropped"); }
+        }
+        Ok(Err(e)) => eprintln!("[{tag} watcher] failed to load image: {e}"),
+        Err(e) => eprintln!("[{tag} watcher] join error: {e}"),
+    }
+}
+
+/// Extensions the `image` crate is built with (see Cargo.toml features).
+const IMAGE_EXTS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "ico", "bmp", "tiff"];
+
+pub(crate) fn is_image_path(p: &Path) -> bool {
+    p.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| IMAGE_EXTS.contains(&e.as_str()))
+}
+
+/// Most recently modified regular file under `dir` (optionally recursive) accepted by `keep`.
+pub(crate) fn newest_file(dir: &Path, recursive: bool, keep: &dyn Fn(&Path) -> bool) -> std::io::Result<Option<PathBuf>> {
+    let mut best: Option<(SystemTime, PathBuf)> = None;
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(d) = stack.pop() {
+        let read = match std::fs::read_dir(&d) {
+            Ok(r) => r,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        for entry in read.flatten() {
+            let path = entry.path();
+            let Ok(meta) = std::fs::metadata(&path) else { continue }; // follows symlinks
+            if meta.is_dir() {
+                if recursive { stack.push(path); }
+                continue;
+            }
+            if !meta.is_file() || !keep(&path) { continue; }
+            let mtime = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            if best.as_ref().is_none_or(|(t, _)| mtime > *t) { best = Some((mtime, path)); }
+        }
+    }
+    Ok(best.map(|(_, p)| p))
+}
@@ -0,0 +1,52 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use watchexec::WatchedPath;
+
+use super::{is_image_path, newest_file, watch_paths, Target, WallpaperEvent};
+
+/// Tails swww's cache (`$XDG_CACHE_HOME/swww`, one file per output) and reports the
+/// image from the most recently written entry. The cache layout changed across swww
+/// releases (plain path, `filter\npath`, versioned sub-directories with binary headers),
+/// so entries are scanned for any absolute path that points at an existing image.
+pub struct SwwwWatcher {
+    cache_dir: PathBuf,
+}
+
+impl SwwwWatcher {
+    pub fn new<P: Into<PathBuf>>(cache_dir: P) -> Self {
+        Self { cache_dir: cache_dir.into() }
+    }
+
+    /// `$XDG_CACHE_HOME/swww`, falling back to `~/.cache/swww`.
+    pub fn default_cache_dir() -> PathBuf {
+        if let Some(xdg) = std::env::var_os("XDG_CACHE_HOME") { return PathBuf::from(xdg).join("swww"); }
+        let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+        home.join(".cache/swww")
+    }
+
+    pub async fn start(self, debounce: Duration) -> Result<mpsc::Receiver<WallpaperEvent>> {
+        let dir = self.cache_dir.clone();
+        let resolve = Arc::new(move || {
+            let Some(entry) = newest_file(&dir, true, &|_| true)? else { return Ok(None) };
+            Ok(parse_cache_entry(&entry)?)
+        });
+        watch_paths("swww", Target::Paths(vec![WatchedPath::recursive(self.cache_dir)]), debounce, resolve).await
+    }
+}
+
+/// Pull the last absolute image path out of a swww cache entry.
+fn parse_cache_entry(entry: &Path) -> std::io::Result<Option<PathBuf>> {
+    let bytes = fs::read(entry)?;
+    let text = String::from_utf8_lossy(&bytes);
+    let found = text
+        .split(|c: char| c.is_control()) // NUL, newlines and binary header bytes
+        .map(str::trim)
+        .filter(|s| s.starts_with('/'))
+        .map(PathBuf::from)
+        .rfind(|p| is_image_path(p) && p.is_file());
+    Ok(found)
+}
@@ -0,0 +1,38 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use super::{watch_paths, Target, WallpaperEvent};
+
+/// Watches a symlink such as `~/.current-wallpaper` and follows it to the image it
+/// points at. Wallpaper scripts usually replace the link (`ln -sf`), which the file
+/// target sees through the link's directory; only events about the link (or the files
+/// it leads to) count, so the rest of `$HOME` stays quiet.
+pub struct SymlinkWatcher {
+    link_path: PathBuf,
+}
+
+impl SymlinkWatcher {
+    pub fn new<P: Into<PathBuf>>(link_path: P) -> Self {
+        Self { link_path: link_path.into() }
+    }
+
+    pub async fn start(self, debounce: Duration) -> Result<mpsc::Receiver<WallpaperEvent>> {
+        let link = self.link_path.clone();
+        let resolve = Arc::new(move || resolve_link_target(&link));
+        watch_paths("symlink", Target::File(self.link_path), debounce, resolve).await
+    }
+}
+
+/// Fully resolve the link; a missing or dangling link means "no wallpaper yet".
+fn resolve_link_target(link: &Path) -> Result<Option<PathBuf>> {
+    match fs::canonicalize(link) {
+        Ok(p) if p.is_file() => Ok(Some(p)),
+        Ok(_) => Ok(None),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
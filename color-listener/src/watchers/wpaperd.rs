@@ -0,0 +1,53 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use watchexec::WatchedPath;
+
+use super::{is_image_path, watch_paths, Target, WallpaperEvent};
+
+/// Follows wpaperd's state directory (`$XDG_STATE_HOME/wpaperd/wallpapers`), where the
+/// daemon keeps one symlink per output pointing at the image it currently shows.
+/// The most recently replaced link wins.
+pub struct WpaperdWatcher {
+    state_dir: PathBuf,
+}
+
+impl WpaperdWatcher {
+    pub fn new<P: Into<PathBuf>>(state_dir: P) -> Self {
+        Self { state_dir: state_dir.into() }
+    }
+
+    /// `$XDG_STATE_HOME/wpaperd/wallpapers`, falling back to `~/.local/state/wpaperd/wallpapers`.
+    pub fn default_state_dir() -> PathBuf {
+        if let Some(xdg) = std::env::var_os("XDG_STATE_HOME") { return PathBuf::from(xdg).join("wpaperd/wallpapers"); }
+        let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+        home.join(".local/state/wpaperd/wallpapers")
+    }
+
+    pub async fn start(self, debounce: Duration) -> Result<mpsc::Receiver<WallpaperEvent>> {
+        let dir = self.state_dir.clone();
+        let resolve = Arc::new(move || newest_link_target(&dir));
+        watch_paths("wpaperd", Target::Paths(vec![WatchedPath::non_recursive(self.state_dir)]), debounce, resolve).await
+    }
+}
+
+/// Ordered by the link's own mtime (not the target's), since swapping wallpapers only replaces links.
+fn newest_link_target(dir: &std::path::Path) -> Result<Option<PathBuf>> {
+    let read = match fs::read_dir(dir) {
+        Ok(r) => r,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut best: Option<(SystemTime, PathBuf)> = None;
+    for entry in read.flatten() {
+        let Ok(meta) = fs::symlink_metadata(entry.path()) else { continue };
+        let Ok(target) = fs::canonicalize(entry.path()) else { continue };
+        if !target.is_file() || !is_image_path(&target) { continue; }
+        let mtime = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        if best.as_ref().is_none_or(|(t, _)| mtime > *t) { best = Some((mtime, target)); }
+    }
+    Ok(best.map(|(_, p)| p))
+}
@@ -16,8 +16,8 @@ impl CalendarPopup {
         // Apply the CSS files.
         utils::apply_css_files(&[ 
             utils::scale_sizes(include_str!("style.css")),
-            include_str!("../../../colors.css"),
         ]);
+        utils::colors::apply_colors_css();
 
         // Create a new GTK Calendar widget.
         let gtk_calendar = GtkCalendar::new();
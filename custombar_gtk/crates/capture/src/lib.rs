@@ -20,8 +20,8 @@ impl Capture {
         // Apply the CSS files.
         utils::apply_css_files(&[
             utils::scale_sizes(include_str!("style.css")),
-            include_str!("../../../colors.css"),
         ]);
+        utils::colors::apply_colors_css();
 
         // Set CSS classes.
         container.set_css_classes(&[
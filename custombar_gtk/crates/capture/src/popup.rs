@@ -175,8 +175,8 @@ impl CapturePopup {
         // Apply the CSS files
         utils::apply_css_files(&[
             utils::scale_sizes(include_str!("style.css")),
-            include_str!("../../../colors.css"),
         ]);
+        utils::colors::apply_colors_css();
 
         Self { popover }
     }
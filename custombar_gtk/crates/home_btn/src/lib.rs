@@ -13,9 +13,9 @@ impl HomeBtn {
 
         // Apply the CSS files
         utils::apply_css_files(&[
-            utils::scale_sizes(include_str!("style.css")), 
-            include_str!("../../../colors.css")
+            utils::scale_sizes(include_str!("style.css")),
         ]);
+        utils::colors::apply_colors_css();
         
         // Set CSS classes
         container.set_css_classes(&["home_btn", "home_btn-box", "background-color", "border-color"]);
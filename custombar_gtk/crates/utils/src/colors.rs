@@ -0,0 +1,91 @@
+use gtk4::gdk::Display;
+use gtk4::glib::MainContext;
+use gtk4::CssProvider;
+use std::cell::OnceCell;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use dotenv::dotenv;
+
+use crate::{logger, socket};
+
+/// Socket that takes `ReloadColors`, sent by color-listener after it rewrote colors.css.
+pub const COLORS_SOCKET: &str = "/tmp/custombar_colors_socket";
+
+/// Shipped colors, used when the runtime file is missing or unreadable.
+const DEFAULT_COLORS: &str = include_str!("../../../colors.css");
+
+thread_local! {
+    static PROVIDER: OnceCell<CssProvider> = const { OnceCell::new() };
+}
+
+/// Runtime colors.css: `CUSTOMBAR_COLORS_CSS` (env or .env), else the dotfiles copy
+/// that color-listener's `custombar` applier writes by default.
+pub fn colors_css_path() -> PathBuf {
+    dotenv().ok();
+    if let Ok(path) = env::var("CUSTOMBAR_COLORS_CSS") {
+        return PathBuf::from(path);
+    }
+    let home = env::var("HOME").unwrap_or_default();
+    PathBuf::from(home).join(".config/dotfiles/custombar_gtk/colors.css")
+}
+
+/// Installs the shared colors provider. Every widget calls this; only the first call
+/// loads the file and starts listening for reloads, so there is a single provider to swap.
+///
+/// Call after gtk4::init().
+pub fn apply_colors_css() {
+    PROVIDER.with(|cell| {
+        if cell.get().is_some() {
+            return;
+        }
+        let provider = CssProvider::new();
+        load(&provider);
+        let display = Display::default().expect("Could not connect to a display.");
+        gtk4::style_context_add_provider_for_display(
+            &display,
+            &provider,
+            gtk4::STYLE_PROVIDER_PRIORITY_USER,
+        );
+        let _ = cell.set(provider);
+        listen_for_reloads();
+    });
+}
+
+/// Re-reads colors.css into the shared provider; GTK restyles every widget using it.
+pub fn reload_colors_css() {
+    PROVIDER.with(|cell| {
+        if let Some(provider) = cell.get() {
+            load(provider);
+            logger::info!("Reloaded colors from {}", colors_css_path().display());
+        }
+    });
+}
+
+fn load(provider: &CssProvider) {
+    let path = colors_css_path();
+    match fs::read_to_string(&path) {
+        Ok(css) => provider.load_from_data(&css),
+        Err(e) => {
+            logger::warn!("Could not read {} ({}), using built-in colors", path.display(), e);
+            provider.load_from_data(DEFAULT_COLORS);
+        }
+    }
+}
+
+// The socket thread can't touch GTK, so it forwards to the main context.
+fn listen_for_reloads() {
+    let (tx, rx) = async_channel::unbounded::<()>();
+    let (_socket_thread, _terminate_flag) = socket::start_unix_socket_with_parser(COLORS_SOCKET, move |message| {
+        if message == "ReloadColors" {
+            let _ = tx.send_blocking(());
+        } else {
+            logger::warn!("Warning: Unknown command '{}'", message);
+        }
+    });
+    MainContext::default().spawn_local(async move {
+        while rx.recv().await.is_ok() {
+            reload_colors_css();
+        }
+    });
+}
@@ -6,6 +6,7 @@ use dotenv::dotenv;
 use gtk4::cairo::Matrix;
 use gtk4::prelude::*;
 
+pub mod colors;
 pub mod logger;
 pub mod socket;
 
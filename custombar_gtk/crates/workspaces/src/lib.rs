@@ -50,8 +50,8 @@ impl Workspace {
         // Apply the CSS files.
         utils::apply_css_files(&[
             utils::scale_sizes(include_str!("style.css")),
-            include_str!("../../../colors.css"),
         ]);
+        utils::colors::apply_colors_css();
 
         // Set CSS classes for styling.
         container.set_css_classes(&[